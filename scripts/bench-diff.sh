@@ -0,0 +1,74 @@
+#!/usr/bin/env bash
+# Times how long `supgit diff` takes to build its file list in a synthetic
+# repository with many changed files. Pass one or more supgit binaries to
+# compare them (e.g. a build of the previous release and the current one):
+#
+#   scripts/bench-diff.sh ./old-supgit target/release/supgit
+#
+# Tune the repository with FILES (tracked files, default 800), LINES (lines
+# per file, default 200) and RUNS (timed runs per case, default 5).
+set -euo pipefail
+
+FILES=${FILES:-800}
+LINES=${LINES:-200}
+RUNS=${RUNS:-5}
+
+if [ "$#" -eq 0 ]; then
+    set -- "$(dirname "$0")/../target/release/supgit"
+fi
+BINARIES=()
+for bin in "$@"; do
+    BINARIES+=("$(realpath "$bin")")
+done
+
+REPO=$(mktemp -d)
+trap 'rm -rf "$REPO"' EXIT
+
+echo "Creating synthetic repository in $REPO ($FILES files x $LINES lines)..."
+cd "$REPO"
+git init -q
+git config user.email bench@example.com
+git config user.name bench
+for i in $(seq 1 "$FILES"); do
+    mkdir -p "dir$((i % 20))"
+    seq 1 "$LINES" | sed "s/^/file $i line /" > "dir$((i % 20))/file$i.txt"
+done
+git add -A
+git commit -qm "initial"
+
+# Half the tracked files get unstaged edits, a quarter get staged edits, and
+# as many new untracked files as modified ones appear.
+for i in $(seq 1 "$FILES"); do
+    file="dir$((i % 20))/file$i.txt"
+    if [ $((i % 2)) -eq 0 ]; then
+        sed -i.bak "1,10s/line/LINE/" "$file" && rm -f "$file.bak"
+    elif [ $((i % 4)) -eq 1 ]; then
+        echo "staged change" >> "$file"
+        git add "$file"
+    fi
+done
+# Untracked files sit next to tracked ones; a wholly untracked directory
+# would be listed as a single entry.
+for i in $(seq 1 $((FILES / 2))); do
+    seq 1 "$LINES" > "dir$((i % 20))/new$i.txt"
+done
+
+# Uses the shell's own `time` so it works without GNU date (`%N` is not
+# available on macOS or BSD).
+time_ms() {
+    local TIMEFORMAT=%3R seconds
+    seconds=$({ time "$@" > /dev/null 2>&1; } 2>&1)
+    awk -v s="${seconds/,/.}" 'BEGIN { printf "%d\n", s * 1000 }'
+}
+
+export SupGIT_SKIP_UPDATE_CHECK=1
+for bin in "${BINARIES[@]}"; do
+    for args in "diff --json" "diff --staged --json"; do
+        total=0
+        for _ in $(seq 1 "$RUNS"); do
+            # shellcheck disable=SC2086
+            total=$((total + $(time_ms "$bin" $args)))
+        done
+        printf '%-40s %-22s %6d ms (avg of %d)\n' "$bin" "$args" $((total / RUNS)) "$RUNS"
+    done
+done
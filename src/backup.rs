@@ -0,0 +1,184 @@
+use std::fs;
+use std::process::Command as StdCommand;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use crate::git::{git_stdout, run_git_silent};
+use crate::journal::format_age;
+use crate::status::get_git_dir;
+
+const BACKUP_REF_PREFIX: &str = "refs/supgit/backups";
+const MAX_BACKUPS: usize = 20;
+const BACKUP_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+pub struct Backup {
+    pub id: u64,
+    pub commit: String,
+    pub timestamp: u64,
+    pub reason: String,
+}
+
+impl Backup {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} ({})",
+            self.id,
+            self.reason,
+            format_age(self.timestamp)
+        )
+    }
+}
+
+/// Saves the working tree under `refs/supgit/backups/<id>`, or `None` if it matches HEAD.
+pub fn create_backup(reason: &str) -> Result<Option<u64>> {
+    let git_dir = get_git_dir()?;
+    let temp_index = git_dir.join("supgit").join("backup-index");
+    if let Some(parent) = temp_index.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let real_index = git_dir.join("index");
+    if real_index.exists() {
+        fs::copy(&real_index, &temp_index)
+            .with_context(|| format!("failed to copy {}", real_index.display()))?;
+    } else {
+        let _ = fs::remove_file(&temp_index);
+    }
+
+    let temp_index_str = temp_index.to_string_lossy().into_owned();
+    let tree = git_with_index(&temp_index_str, &["add", "-A", "--", ":/"])
+        .and_then(|_| git_with_index(&temp_index_str, &["write-tree"]));
+    let _ = fs::remove_file(&temp_index);
+    let tree = tree?;
+
+    let head = git_stdout(&["rev-parse", "--verify", "-q", "HEAD"]);
+    if let Some(ref head) = head
+        && git_stdout(&["rev-parse", &format!("{}^{{tree}}", head)]).as_deref()
+            == Some(tree.as_str())
+    {
+        return Ok(None);
+    }
+
+    let message = format!("supgit backup: {}", reason);
+    let mut commit_args = vec!["commit-tree", tree.as_str(), "-m", message.as_str()];
+    if let Some(ref head) = head {
+        commit_args.push("-p");
+        commit_args.push(head);
+    }
+    let commit = git_stdout(&commit_args).context("failed to create backup commit")?;
+
+    let id = list_backups()?.iter().map(|b| b.id).max().unwrap_or(0) + 1;
+    run_git_silent(&[
+        "update-ref",
+        &format!("{}/{}", BACKUP_REF_PREFIX, id),
+        &commit,
+    ])?;
+
+    prune_backups()?;
+    Ok(Some(id))
+}
+
+pub fn list_backups() -> Result<Vec<Backup>> {
+    let output = StdCommand::new("git")
+        .args([
+            "for-each-ref",
+            "--format=%(refname:lstrip=3)\t%(objectname)\t%(creatordate:unix)\t%(subject)",
+            BACKUP_REF_PREFIX,
+        ])
+        .output()
+        .context("running git for-each-ref")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git for-each-ref failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut backups: Vec<Backup> = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, '\t');
+            let id = parts.next()?.parse().ok()?;
+            let commit = parts.next()?.to_string();
+            let timestamp = parts.next()?.parse().ok()?;
+            let subject = parts.next().unwrap_or_default();
+            let reason = subject
+                .strip_prefix("supgit backup: ")
+                .unwrap_or(subject)
+                .to_string();
+            Some(Backup {
+                id,
+                commit,
+                timestamp,
+                reason,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|b| b.id);
+    Ok(backups)
+}
+
+pub fn find_backup(id: &str) -> Result<Backup> {
+    let id: u64 = id
+        .trim()
+        .trim_start_matches('#')
+        .parse()
+        .with_context(|| format!("invalid backup id '{}'", id))?;
+    list_backups()?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "backup {} not found - run 'supgit restore-backup --list' to see backups",
+                id
+            )
+        })
+}
+
+/// Writes the backup's files into the working tree, leaving the index alone.
+pub fn restore_backup(backup: &Backup) -> Result<()> {
+    run_git_silent(&[
+        "restore",
+        &format!("--source={}", backup.commit),
+        "--worktree",
+        "--",
+        ":/",
+    ])
+}
+
+fn prune_backups() -> Result<()> {
+    let backups = list_backups()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+
+    for (idx, backup) in backups.iter().enumerate() {
+        let expired = now.saturating_sub(backup.timestamp) > BACKUP_MAX_AGE_SECS;
+        if idx < excess || expired {
+            run_git_silent(&[
+                "update-ref",
+                "-d",
+                &format!("{}/{}", BACKUP_REF_PREFIX, backup.id),
+            ])?;
+        }
+    }
+    Ok(())
+}
+
+fn git_with_index(index_file: &str, args: &[&str]) -> Result<String> {
+    let output = StdCommand::new("git")
+        .args(args)
+        .env("GIT_INDEX_FILE", index_file)
+        .output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
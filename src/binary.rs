@@ -0,0 +1,345 @@
+//! Recognises the contents of files git treats as binary: MIME type from
+//! magic bytes, image dimensions from headers, UTF-16 text that only looks
+//! binary because of its NUL bytes, and configured textconv drivers.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Command as StdCommand, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::git::git_stdout;
+use crate::status::get_repo_root;
+
+/// Magic bytes at the start of a file and the MIME type they announce.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"II*\0", "image/tiff"),
+    (b"MM\0*", "image/tiff"),
+    (b"8BPS", "image/vnd.adobe.photoshop"),
+    (b"DDS ", "image/vnd-ms.dds"),
+    (b"\0\0\x01\0", "image/x-icon"),
+    (b"%PDF-", "application/pdf"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"Rar!\x1a\x07", "application/vnd.rar"),
+    (b"SQLite format 3\0", "application/vnd.sqlite3"),
+    (b"\x7fELF", "application/x-elf"),
+    (b"\0asm", "application/wasm"),
+    (b"glTF", "model/gltf-binary"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"ID3", "audio/mpeg"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+    (b"OTTO", "font/otf"),
+    (b"\0\x01\0\0", "font/ttf"),
+];
+
+/// Zip containers are told apart by their extension.
+const ZIP_EXTENSIONS: &[(&str, &str)] = &[
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("jar", "application/java-archive"),
+    ("apk", "application/vnd.android.package-archive"),
+];
+
+/// Guesses the MIME type of `bytes`; `path` only names zip-based formats.
+pub fn sniff_mime(bytes: &[u8], path: &str) -> &'static str {
+    if utf16_encoding(bytes).is_some() {
+        return "text/plain; charset=utf-16";
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        let extension = path.rsplit('.').next().unwrap_or_default();
+        return ZIP_EXTENSIONS
+            .iter()
+            .find(|(ext, _)| extension.eq_ignore_ascii_case(ext))
+            .map_or("application/zip", |(_, mime)| mime);
+    }
+    if bytes.starts_with(b"RIFF") && bytes.len() >= 12 {
+        match &bytes[8..12] {
+            b"WEBP" => return "image/webp",
+            b"WAVE" => return "audio/wav",
+            b"AVI " => return "video/x-msvideo",
+            _ => {}
+        }
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"avif" => "image/avif",
+            b"heic" | b"heix" => "image/heic",
+            b"qt  " => "video/quicktime",
+            _ => "video/mp4",
+        };
+    }
+    if bytes.starts_with(b"BM") && bytes.len() >= 26 {
+        return "image/bmp";
+    }
+    if bytes.starts_with(b"MZ") {
+        return "application/vnd.microsoft.portable-executable";
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map_or("application/octet-stream", |(_, mime)| mime)
+}
+
+/// Width and height of PNG, GIF, JPEG, BMP, WebP and PSD images.
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let le16 = |at: usize| Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32);
+    let be32 = |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+    let le32 = |at: usize| Some(i32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+    let le24 = |at: usize| {
+        let b = bytes.get(at..at + 3)?;
+        Some(u32::from(b[0]) | u32::from(b[1]) << 8 | u32::from(b[2]) << 16)
+    };
+
+    match sniff_mime(bytes, "") {
+        "image/png" => Some((be32(16)?, be32(20)?)),
+        "image/gif" => Some((le16(6)?, le16(8)?)),
+        "image/bmp" => Some((le32(18)?.unsigned_abs(), le32(22)?.unsigned_abs())),
+        "image/vnd.adobe.photoshop" => Some((be32(18)?, be32(14)?)),
+        "image/jpeg" => jpeg_dimensions(bytes),
+        "image/webp" => match bytes.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let b = bytes.get(21..25)?;
+                let (b0, b1, b2, b3) = (b[0] as u32, b[1] as u32, b[2] as u32, b[3] as u32);
+                let width = 1 + (((b1 & 0x3f) << 8) | b0);
+                let height = 1 + (((b3 & 0x0f) << 10) | (b2 << 2) | ((b1 & 0xc0) >> 6));
+                Some((width, height))
+            }
+            b"VP8X" => Some((1 + le24(24)?, 1 + le24(27)?)),
+            _ => None,
+        },
+        _ => None,
+    }
+    .filter(|&(width, height)| width > 0 && height > 0)
+}
+
+/// Reads the image size from the first JPEG start-of-frame segment.
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32);
+    let mut at = 2;
+    while at + 1 < bytes.len() {
+        if bytes[at] != 0xff {
+            return None;
+        }
+        let marker = bytes[at + 1];
+        match marker {
+            // Fill bytes before a marker.
+            0xff => at += 1,
+            // Markers without a length field.
+            0x01 | 0xd0..=0xd9 => at += 2,
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                return Some((be16(at + 7)?, be16(at + 5)?));
+            }
+            _ => at += 2 + be16(at + 2)? as usize,
+        }
+    }
+    None
+}
+
+/// Detects UTF-16 from a byte-order mark or from NULs on alternate bytes.
+pub fn utf16_encoding(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xff, 0xfe]) {
+        return Some("utf-16le");
+    }
+    if bytes.starts_with(&[0xfe, 0xff]) {
+        return Some("utf-16be");
+    }
+    if bytes.len() < 4 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let sample = &bytes[..bytes.len().min(1024)];
+    let zeros_at = |offset: usize| {
+        sample
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let pairs = sample.len() / 2;
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 10 >= pairs * 9 && even == 0 {
+        Some("utf-16le")
+    } else if even * 10 >= pairs * 9 && odd == 0 {
+        Some("utf-16be")
+    } else {
+        None
+    }
+}
+
+/// Decodes UTF-16 text and names its encoding, or `None` if it isn't UTF-16.
+pub fn decode_utf16(bytes: &[u8]) -> Option<(String, &'static str)> {
+    let encoding = utf16_encoding(bytes)?;
+    let units = bytes.chunks_exact(2).map(|pair| {
+        if encoding == "utf-16le" {
+            u16::from_le_bytes([pair[0], pair[1]])
+        } else {
+            u16::from_be_bytes([pair[0], pair[1]])
+        }
+    });
+    let text: String = char::decode_utf16(units).collect::<Result<_, _>>().ok()?;
+    Some((text.trim_start_matches('\u{feff}').to_string(), encoding))
+}
+
+/// Reads up to `limit` bytes of each object (`None` if missing) in one `git cat-file --batch`.
+pub fn blob_headers(objects: &[String], limit: usize) -> Result<Vec<Option<Vec<u8>>>> {
+    if objects.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut child = StdCommand::new("git")
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("running git cat-file --batch")?;
+
+    // Feed the object names from another thread: git answers while it reads,
+    // and could block on a full stdout pipe before taking all of them.
+    let mut stdin = child.stdin.take().context("opening git cat-file stdin")?;
+    let input = objects.join("\n") + "\n";
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let stdout = child.stdout.take().context("opening git cat-file stdout")?;
+    let mut reader = BufReader::new(stdout);
+    let mut headers = Vec::with_capacity(objects.len());
+    for _ in objects {
+        let mut line = String::new();
+        if reader
+            .read_line(&mut line)
+            .context("reading git cat-file")?
+            == 0
+        {
+            bail!("git cat-file --batch ended early");
+        }
+        // "<sha> <type> <size>", or "<name> missing" with no content after it;
+        // the name may contain spaces, so check for that first.
+        let line = line.trim_end();
+        if line.ends_with(" missing") || line.ends_with(" ambiguous") {
+            headers.push(None);
+            continue;
+        }
+        let mut fields = line.rsplitn(3, ' ');
+        let (Some(size), Some(kind)) = (fields.next(), fields.next()) else {
+            bail!("unexpected git cat-file output: {}", line);
+        };
+        let size: u64 = size.parse().context("parsing git cat-file size")?;
+        let mut header = Vec::new();
+        (&mut reader)
+            .take(size.min(limit as u64))
+            .read_to_end(&mut header)
+            .context("reading git cat-file")?;
+        // Skip the rest of the object plus its trailing newline.
+        let rest = size - header.len() as u64 + 1;
+        io::copy(&mut (&mut reader).take(rest), &mut io::sink()).context("reading git cat-file")?;
+        headers.push((kind == "blob").then_some(header));
+    }
+
+    drop(reader);
+    let _ = writer.join();
+    child.wait().context("waiting for git cat-file")?;
+    Ok(headers)
+}
+
+/// Maps root-relative paths whose `diff=<driver>` has a textconv command to that driver.
+pub fn textconv_drivers(paths: &[&str]) -> Result<HashMap<String, String>> {
+    let mut drivers = HashMap::new();
+    if paths.is_empty() {
+        return Ok(drivers);
+    }
+    let output = StdCommand::new("git")
+        .args(["check-attr", "-z", "diff", "--"])
+        .args(paths)
+        .current_dir(get_repo_root()?)
+        .output()
+        .context("running git check-attr")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.split('\0').collect();
+    for record in fields.chunks_exact(3) {
+        let (path, driver) = (record[0], record[2]);
+        if matches!(driver, "set" | "unset" | "unspecified") {
+            continue;
+        }
+        if git_stdout(&["config", &format!("diff.{}.textconv", driver)]).is_some() {
+            drivers.insert(path.to_string(), driver.to_string());
+        }
+    }
+    Ok(drivers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn detects_byte_order_marks() {
+        assert_eq!(utf16_encoding(b"\xff\xfeh\0i\0"), Some("utf-16le"));
+        assert_eq!(utf16_encoding(b"\xfe\xff\0h\0i"), Some("utf-16be"));
+        // A BOM alone is enough, even before any text.
+        assert_eq!(utf16_encoding(b"\xff\xfe"), Some("utf-16le"));
+    }
+
+    #[test]
+    fn detects_mostly_ascii_text_without_a_bom() {
+        assert_eq!(
+            utf16_encoding(&utf16le("hello, world\r\n")),
+            Some("utf-16le")
+        );
+        assert_eq!(
+            utf16_encoding(&utf16be("hello, world\r\n")),
+            Some("utf-16be")
+        );
+        // Latin-1 letters keep a zero high byte too.
+        assert_eq!(utf16_encoding(&utf16le("naïve text")), Some("utf-16le"));
+    }
+
+    #[test]
+    fn rejects_other_content() {
+        assert_eq!(utf16_encoding(b""), None);
+        assert_eq!(utf16_encoding(b"h\0"), None);
+        assert_eq!(utf16_encoding(b"plain ascii text"), None);
+        assert_eq!(utf16_encoding(&utf16le("odd")[..5]), None);
+        assert_eq!(utf16_encoding(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), None);
+        assert_eq!(utf16_encoding(&[0u8; 16]), None);
+        assert_eq!(utf16_encoding(&utf16le("日本語のテキスト")), None);
+    }
+
+    #[test]
+    fn decodes_and_strips_the_bom() {
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend(utf16le("line one\nline two"));
+        assert_eq!(
+            decode_utf16(&bytes),
+            Some(("line one\nline two".to_string(), "utf-16le"))
+        );
+        // An unpaired surrogate after the BOM.
+        assert_eq!(decode_utf16(b"\xff\xfe\x00\xd8a\0"), None);
+    }
+}
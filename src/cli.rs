@@ -11,6 +11,12 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub explain: bool,
 
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
     #[command(subcommand)]
     pub command: Option<SupgitCommand>,
 }
@@ -25,12 +31,16 @@ pub enum SupgitCommand {
         all: bool,
         #[arg(long)]
         tracked: bool,
+        #[arg(short, long)]
+        patch: bool,
     },
     Unstage {
         #[arg(value_name = "PATH")]
         targets: Vec<String>,
         #[arg(long)]
         all: bool,
+        #[arg(short, long)]
+        patch: bool,
     },
     Status {
         #[arg(long)]
@@ -51,15 +61,71 @@ pub enum SupgitCommand {
         amend: bool,
         #[arg(long)]
         no_verify: bool,
+        #[arg(long)]
+        conventional: bool,
+        #[arg(short, long)]
+        edit: bool,
+        #[arg(short, long)]
+        yes: bool,
+        #[arg(long)]
+        allow_secrets: bool,
+        #[arg(long)]
+        force_amend: bool,
+    },
+    Fixup {
+        #[arg(value_name = "COMMIT")]
+        commit: Option<String>,
+        #[arg(long)]
+        allow_secrets: bool,
+    },
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
     },
     Log {
         #[arg(long)]
         short: bool,
+        #[arg(short = 'n', long, value_name = "N")]
+        limit: Option<usize>,
+        #[arg(long)]
+        author: Option<String>,
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+        #[arg(long)]
+        path: Option<String>,
+        #[arg(long, value_name = "PATTERN")]
+        grep: Option<String>,
+        #[arg(long)]
+        graph: bool,
+        #[arg(short, long)]
+        interactive: bool,
+    },
+    Show {
+        #[arg(value_name = "COMMIT")]
+        commit: Option<String>,
+        path: Option<String>,
     },
     Diff {
+        #[arg(value_name = "PATH_OR_BRANCH")]
         path: Option<String>,
         #[arg(long)]
         staged: bool,
+        #[arg(long, value_name = "REV")]
+        from: Option<String>,
+        #[arg(long, value_name = "REV")]
+        to: Option<String>,
+        #[arg(long)]
+        stat: bool,
+        #[arg(short = 'y', long)]
+        side_by_side: bool,
+        #[arg(long)]
+        word_diff: bool,
+        #[arg(long)]
+        line_numbers: bool,
+        #[arg(long)]
+        collapse_whitespace: bool,
     },
     Reset {
         #[arg(long)]
@@ -72,6 +138,8 @@ pub enum SupgitCommand {
         tracked: bool,
         #[arg(long)]
         untracked: bool,
+        #[arg(short, long)]
+        patch: bool,
     },
     Branch {
         #[arg(short, long)]
@@ -82,6 +150,8 @@ pub enum SupgitCommand {
     Push {
         remote: Option<String>,
         branch: Option<String>,
+        #[arg(long)]
+        allow_secrets: bool,
     },
     Pull {
         remote: Option<String>,
@@ -90,6 +160,30 @@ pub enum SupgitCommand {
     Sync {
         remote: Option<String>,
         branch: Option<String>,
+        #[arg(long)]
+        allow_secrets: bool,
+    },
+    Stash {
+        #[arg(value_name = "PATH")]
+        paths: Vec<String>,
+        #[arg(long)]
+        save: bool,
+        #[arg(short, long, value_name = "MSG")]
+        message: Option<String>,
+        #[arg(short, long)]
+        untracked: bool,
+        #[arg(long)]
+        list: bool,
+        #[arg(long)]
+        show: bool,
+        #[arg(long)]
+        apply: bool,
+        #[arg(long)]
+        pop: bool,
+        #[arg(long)]
+        drop: bool,
+        #[arg(long)]
+        clear: bool,
     },
     Clone {
         #[arg(value_name = "URL")]
@@ -98,6 +192,16 @@ pub enum SupgitCommand {
         directory: Option<String>,
     },
     Update,
+    Undo {
+        #[arg(long)]
+        list: bool,
+    },
+    RestoreBackup {
+        #[arg(value_name = "ID")]
+        id: Option<String>,
+        #[arg(long)]
+        list: bool,
+    },
     Alias {
         #[arg(long)]
         dry_run: bool,
@@ -115,3 +219,13 @@ pub enum SupgitCommand {
         sg: bool,
     },
 }
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    Edit {
+        #[arg(short = 'n', long, value_name = "N")]
+        count: Option<usize>,
+        #[arg(long)]
+        force: bool,
+    },
+}
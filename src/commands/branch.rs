@@ -3,8 +3,22 @@ use std::process::Command as StdCommand;
 use anyhow::{Context, Result, bail};
 use dialoguer::{Confirm, Input, Select};
 
-use crate::git::run_git_silent;
-use crate::status::{get_branches, get_current_branch};
+use super::stash::get_stash_entries;
+use crate::git::{git_failure, run_git_quiet, run_git_silent};
+use crate::journal;
+use crate::json::print_json;
+use crate::status::{
+    StatusEntry, get_branch_details, get_branches, get_current_branch, get_status_entries,
+    invalidate_porcelain_cache,
+};
+
+/// Message used for stashes parked on a branch; switching back to that
+/// branch restores them automatically.
+const AUTO_STASH_PREFIX: &str = "supgit auto-stash for ";
+
+pub fn print_branches_json() -> Result<()> {
+    print_json(&get_branch_details()?)
+}
 
 pub fn create_branch(branch_name: &str) -> Result<()> {
     let branch_name = branch_name.trim();
@@ -14,8 +28,13 @@ pub fn create_branch(branch_name: &str) -> Result<()> {
     if branch_name.contains(|c: char| c.is_whitespace()) {
         bail!("branch name cannot contain whitespace");
     }
-    run_git_silent(&["checkout", "-b", branch_name])?;
-    println!("✓ Created and switched to branch '{}'", branch_name);
+    journal::record_creating(
+        &format!("branch --create {}", branch_name),
+        &[format!("refs/heads/{}", branch_name)],
+    )?;
+    if switch_branch(&["checkout", "-b", branch_name], branch_name)? {
+        println!("✓ Created and switched to branch '{}'", branch_name);
+    }
     Ok(())
 }
 
@@ -38,6 +57,7 @@ pub fn delete_branch(branch_name: &str) -> Result<()> {
         bail!("branch '{}' does not exist", branch_name);
     }
 
+    journal::record(&format!("branch --delete {}", branch_name))?;
     run_git_silent(&["branch", "-d", branch_name])?;
     println!("✓ Deleted branch '{}'", branch_name);
     Ok(())
@@ -73,6 +93,7 @@ pub fn delete_branch_interactive() -> Result<()> {
         .interact()?;
 
     if confirmed {
+        journal::record(&format!("branch --delete {}", branch_to_delete))?;
         let output = StdCommand::new("git")
             .args(["branch", "-d", branch_to_delete])
             .output()
@@ -141,8 +162,7 @@ pub fn run_branch_interactive() -> Result<()> {
         }
 
         let normalized_name = branch_name.trim().replace(' ', "-");
-        run_git_silent(&["checkout", "-b", &normalized_name])?;
-        println!("✓ Created and switched to branch '{}'", normalized_name);
+        create_branch(&normalized_name)?;
     } else if selection == branches.len() + 1 {
         delete_branch_interactive()?;
     } else {
@@ -150,10 +170,117 @@ pub fn run_branch_interactive() -> Result<()> {
         if selected_branch == &current {
             println!("Already on branch '{}'.", selected_branch);
         } else {
-            run_git_silent(&["checkout", selected_branch])?;
-            println!("✓ Switched to branch '{}'", selected_branch);
+            journal::record(&format!("checkout {}", selected_branch))?;
+            if switch_branch(&["checkout", selected_branch], selected_branch)? {
+                println!("✓ Switched to branch '{}'", selected_branch);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the checkout in `args`, offering to stash changes that block it; false if cancelled.
+fn switch_branch(args: &[&str], target: &str) -> Result<bool> {
+    let output = StdCommand::new("git")
+        .args(args)
+        .output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.contains("would be overwritten") {
+            return Err(git_failure(args, &stderr));
+        }
+        if !switch_with_auto_stash(args, target, &stderr)? {
+            return Ok(false);
+        }
+    }
+
+    restore_parked_stash(target)?;
+    Ok(true)
+}
+
+fn switch_with_auto_stash(args: &[&str], target: &str, stderr: &str) -> Result<bool> {
+    let current = get_current_branch().unwrap_or_default();
+    println!(
+        "⚠ Your local changes would be overwritten by switching to '{}':",
+        target
+    );
+    for line in stderr.lines().filter(|l| l.starts_with('\t')) {
+        println!("  {}", line.trim());
+    }
+
+    let options = [
+        format!("Stash changes and bring them to '{}'", target),
+        format!(
+            "Stash changes and leave them on '{}' (restored when you switch back)",
+            current
+        ),
+        "Cancel".to_string(),
+    ];
+    let selection = Select::new()
+        .with_prompt("How should SupGIT handle your changes?")
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    let message = match selection {
+        0 => format!("supgit auto-stash while switching to {}", target),
+        1 => format!("{}{}", AUTO_STASH_PREFIX, current),
+        _ => {
+            println!("Cancelled.");
+            return Ok(false);
         }
+    };
+
+    run_git_quiet(&["stash", "push", "--include-untracked", "-m", &message])?;
+    if let Err(e) = run_git_silent(args) {
+        println!("⚠ Switch failed; restoring your changes on '{}'", current);
+        run_git_quiet(&["stash", "pop"])?;
+        return Err(e);
+    }
+
+    if selection == 0 {
+        println!("→ Re-applying your changes on '{}'", target);
+        pop_auto_stash("stash@{0}")?;
+    } else {
+        println!(
+            "→ Changes parked on '{}'; they come back when you switch to it again",
+            current
+        );
+    }
+    Ok(true)
+}
+
+fn restore_parked_stash(branch: &str) -> Result<()> {
+    let expected = format!("{}{}", AUTO_STASH_PREFIX, branch);
+    let parked = get_stash_entries()?
+        .into_iter()
+        .find(|entry| entry.subject.ends_with(&expected));
+    if let Some(entry) = parked {
+        println!(
+            "→ Restoring changes you left on '{}' ({})",
+            branch, entry.age
+        );
+        pop_auto_stash(&entry.name)?;
     }
+    Ok(())
+}
 
+/// Pops an auto-stash after the switch already succeeded, so failures are
+/// reported as warnings instead of errors.
+fn pop_auto_stash(name: &str) -> Result<()> {
+    if run_git_quiet(&["stash", "pop", name]).is_ok() {
+        return Ok(());
+    }
+    invalidate_porcelain_cache();
+    if get_status_entries()?.iter().any(StatusEntry::is_conflicted) {
+        println!("⚠ Re-applying your stashed changes caused conflicts");
+        println!("  Resolve them, then drop the stash with 'supgit stash --drop'.");
+    } else {
+        println!("⚠ Could not re-apply {} automatically", name);
+        println!("  Your changes are still stashed; use 'supgit stash --pop' once ready.");
+    }
     Ok(())
 }
@@ -1,20 +1,52 @@
-use std::process::Command as StdCommand;
+use std::io::IsTerminal;
 
 use anyhow::{Result, bail};
-use dialoguer::{Confirm, Input, Select};
-
-use crate::git::{run_git_quiet, run_git_silent};
-use crate::status::{PorcelainStatus, get_current_branch, get_repo_root};
-
-pub fn run_commit(
-    message: Option<String>,
-    all: bool,
-    staged: bool,
-    unstaged: bool,
-    push: bool,
-    amend: bool,
-    no_verify: bool,
-) -> Result<()> {
+use dialoguer::{Confirm, Editor, Input, Select};
+
+use super::diff::{DiffScope, build_diff_entries, format_selector_item};
+use crate::conventional;
+use crate::git::{git_stdout, run_git_quiet, run_git_silent};
+use crate::journal;
+use crate::policy::{CommitPolicy, strip_comments};
+use crate::scan::{self, scan_staged};
+use crate::status::{
+    PorcelainStatus, StatusEntry, get_current_branch, get_published_refs, get_repo_root,
+    get_staged_files, get_unstaged_files, get_untracked_files,
+};
+
+pub struct CommitOptions {
+    pub message: Option<String>,
+    pub all: bool,
+    pub staged: bool,
+    pub unstaged: bool,
+    pub push: bool,
+    pub amend: bool,
+    pub no_verify: bool,
+    pub conventional: bool,
+    pub edit: bool,
+    pub yes: bool,
+    pub allow_secrets: bool,
+    pub force_amend: bool,
+}
+
+pub fn run_commit(options: CommitOptions) -> Result<()> {
+    let CommitOptions {
+        message,
+        all,
+        staged,
+        unstaged,
+        push,
+        amend,
+        no_verify,
+        conventional,
+        edit,
+        yes,
+        allow_secrets,
+        force_amend,
+    } = options;
+    let conventional = conventional || conventional::enabled_by_config();
+    let policy = CommitPolicy::load()?;
+
     let is_interactive = message.is_none() && !all && !staged && !unstaged;
     let (all, staged, unstaged, commit_msg, push, custom_files) = if is_interactive {
         let scope = Select::new()
@@ -38,15 +70,15 @@ pub fn run_commit(
         let mut custom_files: Vec<String> = Vec::new();
         if scope == 3 {
             let status = PorcelainStatus::parse()?;
-            let files: Vec<&str> = status.all_uncommitted_files();
-            if files.is_empty() {
+            let entries = status.entries();
+            if entries.is_empty() {
                 println!("No files to commit.");
                 return Ok(());
             }
-            let files_owned: Vec<String> = files.iter().map(|s| s.to_string()).collect();
+            let items: Vec<String> = entries.iter().map(StatusEntry::display_path).collect();
             let selected = dialoguer::MultiSelect::new()
                 .with_prompt("Select files to stage")
-                .items(&files_owned)
+                .items(&items)
                 .interact()?;
 
             if selected.is_empty() {
@@ -55,50 +87,76 @@ pub fn run_commit(
             }
 
             for idx in selected {
-                custom_files.push(files_owned[idx].clone());
+                custom_files.push(entries[idx].path.clone());
+                custom_files.extend(entries[idx].orig_path.clone());
             }
         }
 
-        let msg: String = Input::new().with_prompt("Commit message").interact()?;
+        let use_editor = !conventional
+            && (edit
+                || Select::new()
+                    .with_prompt("How would you like to write the message?")
+                    .items(&["Single line", "Subject and body in your editor"])
+                    .default(0)
+                    .interact()?
+                    == 1);
+
+        let msg = if conventional {
+            conventional::compose()?
+        } else if use_editor {
+            let files = files_to_commit(all, unstaged, &custom_files)?;
+            edit_message(&policy, &files)?
+        } else {
+            let template = policy.template_text()?.unwrap_or_default();
+            let template = template.lines().next().unwrap_or_default().to_string();
+            Input::new()
+                .with_prompt("Commit message")
+                .with_initial_text(template)
+                .validate_with(|input: &String| -> Result<(), String> {
+                    match policy.lint(input).into_iter().next() {
+                        Some(problem) => Err(problem),
+                        None => Ok(()),
+                    }
+                })
+                .interact_text()?
+        };
         let should_push = Confirm::new()
             .with_prompt("Push after committing?")
             .default(false)
             .interact()?;
         (all, staged, unstaged, msg, should_push, custom_files)
     } else {
-        let msg = message.unwrap_or_default();
+        let msg = match message {
+            Some(msg) => msg,
+            None if conventional => conventional::compose()?,
+            None if edit => edit_message(&policy, &files_to_commit(all, unstaged, &[])?)?,
+            None => String::new(),
+        };
         (all, staged, unstaged, msg, push, Vec::new())
     };
 
     if commit_msg.trim().is_empty() {
         bail!("commit message cannot be empty");
     }
+    if conventional {
+        conventional::validate_header(&commit_msg)?;
+    }
+    let problems = policy.lint(&commit_msg);
+    if !problems.is_empty() {
+        eprintln!("✗ Commit message does not follow the policy in .supgit.toml:");
+        for problem in &problems {
+            eprintln!("  - {}", problem);
+        }
+        bail!("commit message rejected by .supgit.toml");
+    }
 
     if staged && (all || unstaged) {
         bail!("cannot combine --staged with --all or --unstaged");
     }
 
-    if amend && !no_verify {
-        let has_commits = StdCommand::new("git")
-            .args(["log", "--oneline", "-n", "1"])
-            .output()
-            .ok()
-            .and_then(|o| String::from_utf8(o.stdout).ok())
-            .map(|s| !s.trim().is_empty())
-            .unwrap_or(false);
-
-        if has_commits {
-            eprintln!("⚠ Warning: amending a commit that may have been pushed can cause issues.");
-            eprintln!("  Use --no-verify to skip this check if you're sure.");
-            let confirm = Confirm::new()
-                .with_prompt("Continue with amend?")
-                .default(false)
-                .interact()?;
-            if !confirm {
-                println!("Aborted.");
-                return Ok(());
-            }
-        }
+    if amend && !force_amend && !confirm_amend_published()? {
+        println!("Aborted.");
+        return Ok(());
     }
 
     if all {
@@ -109,13 +167,18 @@ pub fn run_commit(
         println!("→ Staged tracked files");
     } else if !custom_files.is_empty() {
         let repo_root = get_repo_root()?;
-        let mut args = vec!["add".to_string()];
+        let mut args = vec!["add".to_string(), "--".to_string()];
         args.extend(custom_files.iter().cloned());
         let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
         crate::git::run_git_in_dir_silent(&args_refs, &repo_root)?;
         println!("→ Staged {} file(s)", custom_files.len());
     }
 
+    if !review_staged_changes(amend, yes, allow_secrets)? {
+        println!("Commit cancelled; your staged changes were kept.");
+        return Ok(());
+    }
+
     print!("→ Committing");
     if amend {
         print!(" (amend)");
@@ -132,6 +195,7 @@ pub fn run_commit(
     commit_args.push("-m");
     commit_args.push(commit_msg.as_str());
 
+    journal::record(if amend { "commit --amend" } else { "commit" })?;
     run_git_quiet(&commit_args)?;
     println!("✓ Commit created");
 
@@ -142,6 +206,7 @@ pub fn run_commit(
             print!(" to {}", b);
         }
         println!("...");
+        scan::guard_push(None, allow_secrets)?;
         run_git_quiet(&["push"])?;
         println!("✓ Pushed successfully");
     }
@@ -149,3 +214,106 @@ pub fn run_commit(
     println!("Done.");
     Ok(())
 }
+
+/// Files that will end up in the commit once the chosen scope is staged.
+fn files_to_commit(all: bool, unstaged: bool, custom_files: &[String]) -> Result<Vec<String>> {
+    let mut files = get_staged_files()?;
+    if all || unstaged {
+        files.extend(get_unstaged_files()?);
+    }
+    if all {
+        files.extend(get_untracked_files()?);
+    }
+    files.extend(custom_files.iter().cloned());
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Opens `$VISUAL`/`$EDITOR` on the template plus a commented summary of the
+/// files being committed, and returns the message with comments removed.
+fn edit_message(policy: &CommitPolicy, files: &[String]) -> Result<String> {
+    let mut text = policy.template_text()?.unwrap_or_default();
+    text.push_str("\n\n");
+    text.push_str("# Write the subject on the first line, then a blank line and the body.\n");
+    text.push_str("# Lines starting with '#' are ignored; an empty message aborts the commit.\n");
+    text.push_str("#\n");
+    if files.is_empty() {
+        text.push_str("# No files are staged yet.\n");
+    } else {
+        text.push_str("# Files to be committed:\n");
+        for file in files {
+            text.push_str(&format!("#   {}\n", file));
+        }
+    }
+
+    let edited = Editor::new()
+        .extension(".txt")
+        .edit(&text)?
+        .unwrap_or_default();
+    Ok(strip_comments(&edited))
+}
+
+/// Prints the commit's contents and warnings, then asks unless `--yes` or non-interactive.
+fn review_staged_changes(amend: bool, yes: bool, allow_secrets: bool) -> Result<bool> {
+    let entries = build_diff_entries(&DiffScope::Staged)?;
+    let findings = scan_staged()?;
+    if yes && findings.is_empty() {
+        return Ok(true);
+    }
+
+    if !yes {
+        if entries.is_empty() && !amend {
+            bail!("nothing staged to commit; stage files with 'supgit stage' or pass --all");
+        }
+        println!("Review commit:");
+        for entry in &entries {
+            println!("  {}", format_selector_item(entry));
+        }
+        let additions: usize = entries.iter().filter_map(|e| e.additions).sum();
+        let deletions: usize = entries.iter().filter_map(|e| e.deletions).sum();
+        println!("  {} file(s), +{} -{}", entries.len(), additions, deletions);
+    }
+
+    for finding in findings.iter().filter(|f| !f.blocking) {
+        eprintln!("⚠ {}: {}", finding.location(), finding.message);
+    }
+    scan::enforce(&findings, allow_secrets, "commit")?;
+
+    if yes || !std::io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    let confirmed = Confirm::new()
+        .with_prompt("Create this commit?")
+        .default(findings.is_empty())
+        .interact()?;
+    Ok(confirmed)
+}
+
+/// Asks before amending a HEAD that is already on a remote; false if the user backs out.
+fn confirm_amend_published() -> Result<bool> {
+    if git_stdout(&["rev-parse", "--verify", "--quiet", "HEAD"]).is_none() {
+        return Ok(true);
+    }
+    let published = get_published_refs("HEAD")?;
+    if published.is_empty() {
+        return Ok(true);
+    }
+
+    eprintln!(
+        "⚠ The commit you are amending is already pushed to {}.",
+        published.join(", ")
+    );
+    eprintln!(
+        "  Amending rewrites it, so you will need a force push and collaborators may be affected."
+    );
+    eprintln!("  Pass --force-amend to skip this question.");
+    if !std::io::stdin().is_terminal() {
+        bail!("refusing to amend a published commit without --force-amend");
+    }
+    let confirm = Confirm::new()
+        .with_prompt("Amend the published commit anyway?")
+        .default(false)
+        .interact()?;
+    Ok(confirm)
+}
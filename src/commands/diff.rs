@@ -1,14 +1,29 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::Command as StdCommand;
 
 use anyhow::{Context, Result, bail};
-use dialoguer::Select;
+use dialoguer::{Confirm, Select};
+use serde::Serialize;
 
-use crate::status::get_porcelain_lines;
+use super::reset::discard_entries;
+use super::stage::stage_paths;
+use super::unstage::unstage_paths;
+use crate::binary::{
+    blob_headers, decode_utf16, image_dimensions, sniff_mime, textconv_drivers, utf16_encoding,
+};
+use crate::git::git_stdout;
+use crate::json::print_json;
+use crate::pager;
+use crate::render::{RenderOptions, render_diff};
+use crate::scan::{blob_sizes, format_size};
+use crate::status::{FileState, StatusEntry, get_repo_root, get_status_entries};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum ChangeType {
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeType {
     Modified,
     Created,
     Deleted,
@@ -34,129 +49,858 @@ impl ChangeType {
     }
 }
 
-struct FileDiffEntry {
-    display_path: String,
-    git_path: String,
-    change_type: ChangeType,
-    additions: Option<usize>,
-    deletions: Option<usize>,
+#[derive(Serialize)]
+pub struct FileDiffEntry {
+    #[serde(rename = "path")]
+    pub git_path: String,
+    pub orig_path: Option<String>,
+    #[serde(skip)]
+    pub display_path: String,
+    #[serde(rename = "change")]
+    pub change_type: ChangeType,
+    pub additions: Option<usize>,
+    pub deletions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary: Option<BinaryDetails>,
 }
 
-pub fn run_diff(path: Option<String>, staged: bool) -> Result<()> {
-    if let Some(path) = path {
-        show_diff_for_path(&path, staged, false)?;
-        return Ok(());
+/// Bytes read from each side of a binary file; JPEG sizes can sit this deep.
+const HEADER_BYTES: usize = 64 * 1024;
+
+/// What SupGIT can tell about a file git considers binary.
+#[derive(Serialize)]
+pub struct BinaryDetails {
+    pub mime: &'static str,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_dimensions: Option<(u32, u32)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_dimensions: Option<(u32, u32)>,
+    /// Set when both sides are UTF-16 text, which is decoded before diffing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<&'static str>,
+    /// The `diff.<driver>.textconv` driver git converts the file with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub textconv: Option<String>,
+}
+
+impl BinaryDetails {
+    /// e.g. `image/png, 12.0 KB → 14.1 KB (+2.1 KB), 64×64 → 128×128`.
+    fn summary(&self) -> String {
+        let mut parts = vec![self.mime.to_string()];
+        parts.extend(self.size_change());
+        parts.extend(self.dimensions_change());
+        parts.join(", ")
+    }
+
+    fn size_change(&self) -> Option<String> {
+        match (self.old_size, self.new_size) {
+            (Some(old), Some(new)) if old == new => Some(format_size(new)),
+            (Some(old), Some(new)) => {
+                let sign = if new > old { '+' } else { '-' };
+                Some(format!(
+                    "{} → {} ({}{})",
+                    format_size(old),
+                    format_size(new),
+                    sign,
+                    format_size(new.abs_diff(old))
+                ))
+            }
+            (old, new) => old.or(new).map(format_size),
+        }
     }
 
-    run_diff_selector(staged)
+    fn dimensions_change(&self) -> Option<String> {
+        let format = |d: Option<(u32, u32)>| d.map(|(w, h)| format!("{}×{}", w, h));
+        match (format(self.old_dimensions), format(self.new_dimensions)) {
+            (Some(old), Some(new)) if old != new => Some(format!("{} → {}", old, new)),
+            (old, new) => new.or(old),
+        }
+    }
 }
 
-fn run_diff_selector(staged: bool) -> Result<()> {
-    let entries = build_diff_entries(staged)?;
-    if entries.is_empty() {
+/// What a diff compares: the worktree against the index, the index against
+/// HEAD, or one revision against another (or against the worktree).
+pub enum DiffScope {
+    Unstaged,
+    Staged,
+    Range { from: String, to: Option<String> },
+}
+
+impl DiffScope {
+    fn git_args(&self) -> Vec<&str> {
+        match self {
+            DiffScope::Unstaged => Vec::new(),
+            DiffScope::Staged => vec!["--staged"],
+            DiffScope::Range { from, to } => std::iter::once(from.as_str())
+                .chain(to.as_deref())
+                .collect(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            DiffScope::Unstaged => "unstaged".to_string(),
+            DiffScope::Staged => "staged".to_string(),
+            DiffScope::Range { from, to } => format!(
+                "{}..{}",
+                short_rev(from),
+                to.as_deref().map_or("working tree".to_string(), short_rev)
+            ),
+        }
+    }
+}
+
+pub struct DiffOptions {
+    pub path: Option<String>,
+    pub staged: bool,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub stat: bool,
+    pub render: RenderOptions,
+}
+
+pub fn run_diff(options: DiffOptions, json: bool) -> Result<()> {
+    let (scope, path) =
+        resolve_scope(options.path, options.staged, options.from, options.to, json)?;
+    let root_path = path.as_deref().map(repo_relative_path);
+
+    if json {
+        return print_json(&build_entries(&scope, root_path.as_deref())?);
+    }
+
+    if options.stat {
+        return print_stat(&scope, root_path.as_deref());
+    }
+
+    if let (Some(path), Some(root_path)) = (path, root_path) {
+        // A single changed file gets the same treatment as in the selector
+        // (binary summaries, decoded text); other pathspecs go to git as is.
+        if let Some(entry) = build_entries(&scope, Some(&root_path))?.first() {
+            return show_diff_for_entry(&scope, entry, options.render);
+        }
+        return show_tracked_diff(&scope, &[path.as_str()], Path::new("."), options.render);
+    }
+
+    run_diff_selector(&scope, options.render)
+}
+
+/// Resolves a path typed relative to the current directory (`./a`, `../b`)
+/// into the repository-root-relative form git reports paths in.
+fn repo_relative_path(path: &str) -> String {
+    let prefix = git_stdout(&["rev-parse", "--show-prefix"]).unwrap_or_default();
+    let joined = format!("{}{}", prefix, path);
+    let mut parts: Vec<&str> = Vec::new();
+    for part in joined.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Turns the flags into a scope; a lone revision argument diffs from its merge-base with HEAD.
+fn resolve_scope(
+    path: Option<String>,
+    staged: bool,
+    from: Option<String>,
+    to: Option<String>,
+    quiet: bool,
+) -> Result<(DiffScope, Option<String>)> {
+    if staged && (from.is_some() || to.is_some()) {
+        bail!("cannot combine --staged with --from or --to");
+    }
+
+    if from.is_some() || to.is_some() {
+        let from = from.unwrap_or_else(|| "HEAD".to_string());
+        for rev in std::iter::once(&from).chain(to.as_ref()) {
+            verify_revision(rev)?;
+        }
+        return Ok((DiffScope::Range { from, to }, path));
+    }
+
+    if let Some(ref target) = path
+        && !Path::new(target).exists()
+        && resolve_commit(target).is_some()
+    {
         if staged {
-            println!("No staged files to diff.");
-        } else {
-            println!("No unstaged files to diff.");
+            bail!("cannot combine --staged with a branch comparison");
         }
-        return Ok(());
+        let Some(base) = git_stdout(&["merge-base", "HEAD", target]) else {
+            bail!("HEAD and '{}' have no common history to compare", target);
+        };
+        if !quiet {
+            println!(
+                "→ Comparing HEAD with {} since they diverged at {}",
+                target,
+                short_rev(&base)
+            );
+        }
+        let scope = DiffScope::Range {
+            from: base,
+            to: Some("HEAD".to_string()),
+        };
+        return Ok((scope, None));
     }
 
-    let mut items: Vec<String> = entries.iter().map(format_selector_item).collect();
-    items.push("Cancel".to_string());
+    let scope = if staged {
+        DiffScope::Staged
+    } else {
+        DiffScope::Unstaged
+    };
+    Ok((scope, path))
+}
+
+fn resolve_commit(rev: &str) -> Option<String> {
+    git_stdout(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", rev),
+    ])
+}
+
+fn verify_revision(rev: &str) -> Result<()> {
+    if resolve_commit(rev).is_none() {
+        bail!("'{}' is not a branch, tag or commit", rev);
+    }
+    Ok(())
+}
 
-    let prompt = if staged {
-        "Select a staged file to view its diff"
+fn short_rev(rev: &str) -> String {
+    if rev.len() == 40 && rev.chars().all(|c| c.is_ascii_hexdigit()) {
+        rev[..7].to_string()
     } else {
-        "Select an unstaged file to view its diff"
+        rev.to_string()
+    }
+}
+
+fn print_stat(scope: &DiffScope, path: Option<&str>) -> Result<()> {
+    let entries = build_entries(scope, path)?;
+    if entries.is_empty() {
+        print_no_changes(scope);
+        return Ok(());
+    }
+
+    for entry in &entries {
+        println!("  {}", format_selector_item(entry));
+    }
+    let additions: usize = entries.iter().filter_map(|e| e.additions).sum();
+    let deletions: usize = entries.iter().filter_map(|e| e.deletions).sum();
+    println!("  {} file(s), +{} -{}", entries.len(), additions, deletions);
+    Ok(())
+}
+
+fn print_no_changes(scope: &DiffScope) {
+    match scope {
+        DiffScope::Unstaged => println!("No unstaged files to diff."),
+        DiffScope::Staged => println!("No staged files to diff."),
+        DiffScope::Range { .. } => println!("No differences in {}.", scope.describe()),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReviewAction {
+    Next,
+    Previous,
+    List,
+    Stage,
+    Unstage,
+    Discard,
+}
+
+/// Lets the user walk through every changed file: the list marks files
+/// already viewed, and after each diff they can move on, go back, or stage,
+/// unstage or discard the file in place.
+fn run_diff_selector(scope: &DiffScope, render: RenderOptions) -> Result<()> {
+    let prompt = match scope {
+        DiffScope::Unstaged => "Select an unstaged file to view its diff".to_string(),
+        DiffScope::Staged => "Select a staged file to view its diff".to_string(),
+        DiffScope::Range { .. } => format!(
+            "Select a file changed in {} to view its diff",
+            scope.describe()
+        ),
     };
 
-    let selection = Select::new()
-        .with_prompt(prompt)
-        .items(&items)
+    let mut viewed: HashSet<String> = HashSet::new();
+    let mut cursor = 0;
+    let mut first = true;
+    loop {
+        let entries = build_diff_entries(scope)?;
+        if entries.is_empty() {
+            if first {
+                print_no_changes(scope);
+            } else {
+                println!("✓ Nothing left to review.");
+            }
+            return Ok(());
+        }
+        first = false;
+        cursor = cursor.min(entries.len() - 1);
+
+        let mut items: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                let mark = if viewed.contains(&entry.git_path) {
+                    "✓"
+                } else {
+                    " "
+                };
+                format!("{} {}", mark, format_selector_item(entry))
+            })
+            .collect();
+        items.push("Done".to_string());
+
+        let selection = Select::new()
+            .with_prompt(&prompt)
+            .items(&items)
+            .default(cursor)
+            .interact()?;
+        if selection == entries.len() {
+            return Ok(());
+        }
+        cursor = selection;
+
+        loop {
+            let entry = &entries[cursor];
+            show_diff_for_entry(scope, entry, render)?;
+            viewed.insert(entry.git_path.clone());
+
+            match prompt_review_action(scope, entry, cursor, entries.len())? {
+                ReviewAction::Next => cursor += 1,
+                ReviewAction::Previous => cursor -= 1,
+                ReviewAction::List => break,
+                action => {
+                    apply_review_action(scope, action, entry)?;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn prompt_review_action(
+    scope: &DiffScope,
+    entry: &FileDiffEntry,
+    position: usize,
+    total: usize,
+) -> Result<ReviewAction> {
+    let mut actions: Vec<(&str, ReviewAction)> = Vec::new();
+    if position + 1 < total {
+        actions.push(("Next file", ReviewAction::Next));
+    }
+    if position > 0 {
+        actions.push(("Previous file", ReviewAction::Previous));
+    }
+    actions.push(("Back to the list", ReviewAction::List));
+    match scope {
+        DiffScope::Unstaged => {
+            actions.push(("Stage this file", ReviewAction::Stage));
+            actions.push(("Discard these changes", ReviewAction::Discard));
+        }
+        DiffScope::Staged => {
+            actions.push(("Unstage this file", ReviewAction::Unstage));
+            actions.push((
+                "Discard this file's staged and unstaged changes",
+                ReviewAction::Discard,
+            ));
+        }
+        DiffScope::Range { .. } => {}
+    }
+
+    let labels: Vec<&str> = actions.iter().map(|(label, _)| *label).collect();
+    let choice = Select::new()
+        .with_prompt(format!(
+            "{} ({}/{})",
+            entry.display_path,
+            position + 1,
+            total
+        ))
+        .items(&labels)
         .default(0)
         .interact()?;
+    Ok(actions[choice].1)
+}
 
-    if selection == entries.len() {
-        println!("No file selected.");
-        return Ok(());
-    }
+fn apply_review_action(
+    scope: &DiffScope,
+    action: ReviewAction,
+    entry: &FileDiffEntry,
+) -> Result<()> {
+    let mut paths: Vec<String> = entry.orig_path.iter().cloned().collect();
+    paths.push(entry.git_path.clone());
 
-    let selected = &entries[selection];
-    show_diff_for_path(
-        &selected.git_path,
-        staged,
-        selected.change_type == ChangeType::Untracked,
-    )?;
+    match action {
+        ReviewAction::Stage => {
+            stage_paths(&paths)?;
+            println!("✓ Staged {}", entry.display_path);
+        }
+        ReviewAction::Unstage => {
+            unstage_paths(&paths)?;
+            println!("✓ Unstaged {}", entry.display_path);
+        }
+        ReviewAction::Discard => {
+            let confirm = Confirm::new()
+                .with_prompt(format!("Discard changes to {}?", entry.display_path))
+                .default(false)
+                .interact()?;
+            if !confirm {
+                return Ok(());
+            }
+            let status_entries = get_status_entries()?;
+            let Some(status) = status_entries.iter().find(|s| s.path == entry.git_path) else {
+                bail!("'{}' no longer has changes", entry.display_path);
+            };
+            // In the unstaged view only the worktree side is thrown away.
+            discard_entries(&[status], matches!(scope, DiffScope::Unstaged))?;
+            println!("✓ Discarded changes to {}", entry.display_path);
+        }
+        ReviewAction::Next | ReviewAction::Previous | ReviewAction::List => {}
+    }
     Ok(())
 }
 
-fn build_diff_entries(staged: bool) -> Result<Vec<FileDiffEntry>> {
-    let porcelain_entries = get_porcelain_lines()?;
-    let mut entries = Vec::new();
+pub fn build_diff_entries(scope: &DiffScope) -> Result<Vec<FileDiffEntry>> {
+    build_entries(scope, None)
+}
+
+/// Builds the entries of every changed file, or only of the file at `path`
+/// (relative to the repository root, or as displayed for a rename) so a
+/// single-file diff doesn't count lines across the whole tree.
+fn build_entries(scope: &DiffScope, path: Option<&str>) -> Result<Vec<FileDiffEntry>> {
+    let staged = match scope {
+        DiffScope::Unstaged => false,
+        DiffScope::Staged => true,
+        DiffScope::Range { .. } => return build_range_diff_entries(scope, path),
+    };
 
-    for (status, path) in porcelain_entries {
+    let mut entries = Vec::new();
+    for status in get_status_entries()? {
+        if let Some(path) = path
+            && status.path != path
+            && status.display_path() != path
+            && status.orig_path.as_deref() != Some(path)
+        {
+            continue;
+        }
         if let Some(change_type) = classify_change(&status, staged) {
-            let git_path = canonical_git_path(&path);
-            let (additions, deletions) =
-                get_line_change_counts(&git_path, staged, change_type == ChangeType::Untracked)?;
             entries.push(FileDiffEntry {
-                display_path: path,
-                git_path,
+                display_path: status.display_path(),
+                git_path: status.path,
+                orig_path: status.orig_path,
                 change_type,
-                additions,
-                deletions,
+                additions: Some(0),
+                deletions: Some(0),
+                binary: None,
             });
         }
     }
+    if entries.is_empty() {
+        return Ok(entries);
+    }
+
+    // One numstat call covers every tracked file; untracked files aren't in
+    // the index, so their lines are counted from disk. A single file also
+    // passes its old name so git still pairs up the rename.
+    let pathspecs: Vec<String> = match path {
+        Some(_) => entries
+            .iter()
+            .flat_map(|e| std::iter::once(&e.git_path).chain(&e.orig_path))
+            .map(|p| format!(":(top,literal){}", p))
+            .collect(),
+        None => Vec::new(),
+    };
+    let counts = parse_numstat(&git_diff_output(scope, "--numstat", &pathspecs)?);
+    let untracked: Vec<&str> = entries
+        .iter()
+        .filter(|e| e.change_type == ChangeType::Untracked)
+        .map(|e| e.git_path.as_str())
+        .collect();
+    let repo_root = get_repo_root()?;
+    let untracked_counts = count_untracked_lines(Path::new(&repo_root), &untracked)?;
+    let mut untracked_counts = untracked_counts.into_iter();
+
+    for entry in &mut entries {
+        if entry.change_type == ChangeType::Untracked {
+            entry.additions = untracked_counts.next().flatten();
+        } else if let Some(&(additions, deletions)) = counts.get(&entry.git_path) {
+            entry.additions = additions;
+            entry.deletions = deletions;
+        }
+    }
 
+    describe_binary_entries(scope, &mut entries, Path::new(&repo_root))?;
     Ok(entries)
 }
 
-fn classify_change(status: &str, staged: bool) -> Option<ChangeType> {
-    let chars: Vec<char> = status.chars().collect();
-    let x = chars.first().copied().unwrap_or(' ');
-    let y = chars.get(1).copied().unwrap_or(' ');
+/// Files changed between two revisions, or a revision and the worktree.
+fn build_range_diff_entries(scope: &DiffScope, path: Option<&str>) -> Result<Vec<FileDiffEntry>> {
+    let pathspecs: Vec<String> = path
+        .map(|p| format!(":(top,literal){}", p))
+        .into_iter()
+        .collect();
+    let name_status = git_diff_output(scope, "--name-status", &pathspecs)?;
+    let numstat = git_diff_output(scope, "--numstat", &pathspecs)?;
 
-    if !staged && x == '?' && y == '?' {
-        return Some(ChangeType::Untracked);
+    let mut fields = name_status.split('\0').filter(|f| !f.is_empty());
+    let mut entries = Vec::new();
+    while let Some(status) = fields.next() {
+        let change_type = match status.chars().next() {
+            Some('A') => ChangeType::Created,
+            Some('D') => ChangeType::Deleted,
+            Some('R') => ChangeType::Renamed,
+            Some('C') => ChangeType::Copied,
+            Some('T') => ChangeType::TypeChanged,
+            Some('U') => ChangeType::Unmerged,
+            _ => ChangeType::Modified,
+        };
+        let Some(path) = fields.next() else {
+            break;
+        };
+        let (display_path, git_path, orig_path) =
+            if matches!(change_type, ChangeType::Renamed | ChangeType::Copied) {
+                let new_path = fields.next().unwrap_or(path);
+                (
+                    format!("{} -> {}", path, new_path),
+                    new_path.to_string(),
+                    Some(path.to_string()),
+                )
+            } else {
+                (path.to_string(), path.to_string(), None)
+            };
+        entries.push(FileDiffEntry {
+            display_path,
+            git_path,
+            orig_path,
+            change_type,
+            additions: Some(0),
+            deletions: Some(0),
+            binary: None,
+        });
     }
 
-    let code = if staged { x } else { y };
-    match code {
-        'M' => Some(ChangeType::Modified),
-        'A' => Some(ChangeType::Created),
-        'D' => Some(ChangeType::Deleted),
-        'R' => Some(ChangeType::Renamed),
-        'C' => Some(ChangeType::Copied),
-        'T' => Some(ChangeType::TypeChanged),
-        'U' => Some(ChangeType::Unmerged),
-        _ => None,
+    // The pathspec also matches directories; only the file itself counts.
+    if let Some(path) = path {
+        entries.retain(|e| e.git_path == path || e.display_path == path);
+    }
+    let counts = parse_numstat(&numstat);
+    for entry in &mut entries {
+        if let Some(&(additions, deletions)) = counts.get(&entry.git_path) {
+            entry.additions = additions;
+            entry.deletions = deletions;
+        }
+    }
+
+    let repo_root = get_repo_root()?;
+    describe_binary_entries(scope, &mut entries, Path::new(&repo_root))?;
+    Ok(entries)
+}
+
+/// Describes the files numstat reports as binary, counting lines of their text form when there is one.
+fn describe_binary_entries(
+    scope: &DiffScope,
+    entries: &mut [FileDiffEntry],
+    root: &Path,
+) -> Result<()> {
+    let binary: Vec<&str> = entries
+        .iter()
+        .filter(|e| e.additions.is_none())
+        .map(|e| e.git_path.as_str())
+        .collect();
+    if binary.is_empty() {
+        return Ok(());
+    }
+    let mut drivers = textconv_drivers(&binary)?;
+
+    let sides: Vec<(Side, Side)> = entries
+        .iter()
+        .filter(|e| e.additions.is_none())
+        .map(|e| entry_sides(scope, e, root))
+        .collect();
+    let objects: Vec<String> = sides
+        .iter()
+        .flat_map(|(old, new)| [old, new])
+        .filter_map(|side| match side {
+            Side::Object(spec) => Some(spec.clone()),
+            _ => None,
+        })
+        .collect();
+    let sizes = blob_sizes(&objects)?;
+    let headers = blob_headers(&objects, HEADER_BYTES)?;
+    let blobs: HashMap<&str, (Option<u64>, Option<Vec<u8>>)> = objects
+        .iter()
+        .map(String::as_str)
+        .zip(sizes.into_iter().zip(headers))
+        .collect();
+    let peek = |side: &Side| -> Option<(u64, Vec<u8>)> {
+        match side {
+            Side::Missing => None,
+            Side::Worktree(path) => {
+                let size = fs::metadata(path).ok().filter(|m| m.is_file())?.len();
+                let mut header = Vec::new();
+                fs::File::open(path)
+                    .ok()?
+                    .take(HEADER_BYTES as u64)
+                    .read_to_end(&mut header)
+                    .ok()?;
+                Some((size, header))
+            }
+            Side::Object(spec) => match blobs.get(spec.as_str())? {
+                (Some(size), Some(header)) => Some((*size, header.clone())),
+                _ => None,
+            },
+        }
+    };
+
+    let binary_entries = entries.iter_mut().filter(|e| e.additions.is_none());
+    for (entry, (old_side, new_side)) in binary_entries.zip(&sides) {
+        let (old, new) = (peek(old_side), peek(new_side));
+        if old.is_none() && new.is_none() {
+            continue;
+        }
+        let sample = new.as_ref().or(old.as_ref()).map_or(&[][..], |(_, h)| h);
+        let old_encoding = old.as_ref().map(|(_, h)| utf16_encoding(h));
+        let new_encoding = new.as_ref().map(|(_, h)| utf16_encoding(h));
+        let encoding = match (old_encoding, new_encoding) {
+            (Some(None), _) | (_, Some(None)) | (None, None) => None,
+            (Some(Some(encoding)), _) | (None, Some(Some(encoding))) => Some(encoding),
+        };
+
+        let mut details = BinaryDetails {
+            mime: sniff_mime(sample, &entry.git_path),
+            old_size: old.as_ref().map(|(size, _)| *size),
+            new_size: new.as_ref().map(|(size, _)| *size),
+            old_dimensions: old.as_ref().and_then(|(_, h)| image_dimensions(h)),
+            new_dimensions: new.as_ref().and_then(|(_, h)| image_dimensions(h)),
+            encoding,
+            textconv: drivers.remove(&entry.git_path),
+        };
+
+        // A configured driver wins over SupGIT's own UTF-16 decoding.
+        let patch = if details.textconv.is_some() {
+            Some(textconv_diff(scope, entry, false)?)
+        } else if details.encoding.is_some() {
+            let decoded = decoded_diff_for_sides(&entry.git_path, old_side, new_side, false)?;
+            if decoded.is_none() {
+                details.encoding = None;
+            }
+            decoded
+        } else {
+            None
+        };
+        if let Some(patch) = patch {
+            let (additions, deletions) = count_patch_lines(&patch);
+            entry.additions = Some(additions);
+            entry.deletions = Some(deletions);
+        }
+        entry.binary = Some(details);
+    }
+    Ok(())
+}
+
+/// Where one side of a changed file lives in a diff scope.
+enum Side {
+    /// The file doesn't exist on this side (created or deleted files).
+    Missing,
+    Worktree(PathBuf),
+    /// An object name such as `:path` or `HEAD:path` for `git cat-file`.
+    Object(String),
+}
+
+impl Side {
+    /// The full contents, for the few cases that need more than a header.
+    fn read(&self) -> Option<Vec<u8>> {
+        match self {
+            Side::Missing => None,
+            Side::Worktree(path) => fs::read(path).ok(),
+            Side::Object(spec) => {
+                let output = StdCommand::new("git")
+                    .args(["cat-file", "blob", spec])
+                    .output()
+                    .ok()?;
+                output.status.success().then_some(output.stdout)
+            }
+        }
     }
 }
 
-fn canonical_git_path(path: &str) -> String {
-    if let Some((_, new_path)) = path.split_once(" -> ") {
-        return new_path.to_string();
+fn entry_sides(scope: &DiffScope, entry: &FileDiffEntry, root: &Path) -> (Side, Side) {
+    let old_path = entry.orig_path.as_deref().unwrap_or(&entry.git_path);
+    let path = entry.git_path.as_str();
+    let worktree = || Side::Worktree(root.join(path));
+    let object = |rev: &str, path: &str| Side::Object(format!("{}:{}", rev, path));
+    let (old, new) = match scope {
+        _ if entry.change_type == ChangeType::Untracked => (Side::Missing, worktree()),
+        DiffScope::Unstaged => (object("", old_path), worktree()),
+        DiffScope::Staged => (object("HEAD", old_path), object("", path)),
+        DiffScope::Range { from, to } => (
+            object(from, old_path),
+            match to {
+                Some(to) => object(to, path),
+                None => worktree(),
+            },
+        ),
+    };
+    match entry.change_type {
+        ChangeType::Created => (Side::Missing, new),
+        ChangeType::Deleted => (old, Side::Missing),
+        _ => (old, new),
     }
-    path.to_string()
 }
 
-fn get_line_change_counts(
+/// Diffs both sides decoded from UTF-16, or `None` if one doesn't decode.
+fn decoded_diff_for_sides(
     path: &str,
-    staged: bool,
-    untracked: bool,
-) -> Result<(Option<usize>, Option<usize>)> {
-    if untracked {
-        return Ok((count_file_lines(path).ok(), Some(0)));
+    old: &Side,
+    new: &Side,
+    color: bool,
+) -> Result<Option<String>> {
+    let decode = |side: &Side| -> Option<Option<String>> {
+        match side.read() {
+            Some(bytes) => decode_utf16(&bytes).map(|(text, _)| Some(text)),
+            None => Some(None),
+        }
+    };
+    let (Some(old), Some(new)) = (decode(old), decode(new)) else {
+        return Ok(None);
+    };
+    decoded_diff(path, old.as_deref(), new.as_deref(), color).map(Some)
+}
+
+/// Counts added and removed lines in a patch, ignoring the file headers.
+fn count_patch_lines(patch: &str) -> (usize, usize) {
+    let mut counts = (0, 0);
+    let mut in_hunk = false;
+    for line in patch.lines() {
+        if line.starts_with("diff ") {
+            in_hunk = false;
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+        } else if in_hunk && line.starts_with('+') {
+            counts.0 += 1;
+        } else if in_hunk && line.starts_with('-') {
+            counts.1 += 1;
+        }
     }
+    counts
+}
 
-    let mut args = vec!["diff", "--numstat"];
-    if staged {
-        args.push("--staged");
+/// Diffs decoded text written to `a/<path>` and `b/<path>` in a scratch directory.
+fn decoded_diff(path: &str, old: Option<&str>, new: Option<&str>, color: bool) -> Result<String> {
+    let scratch = std::env::temp_dir().join(format!("supgit-decoded-{}", std::process::id()));
+    let mut sides = Vec::new();
+    for (side, text) in [("a", old), ("b", new)] {
+        match text {
+            Some(text) => {
+                let file = scratch.join(side).join(path);
+                if let Some(parent) = file.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("creating {}", parent.display()))?;
+                }
+                fs::write(&file, text).with_context(|| format!("writing {}", file.display()))?;
+                sides.push(format!("{}/{}", side, path));
+            }
+            None => sides.push("/dev/null".to_string()),
+        }
+    }
+
+    let output = StdCommand::new("git")
+        .current_dir(&scratch)
+        .args([
+            "diff",
+            "--no-index",
+            "--no-prefix",
+            if color {
+                "--color=always"
+            } else {
+                "--no-color"
+            },
+            "--",
+            &sides[0],
+            &sides[1],
+        ])
+        .output()
+        .context("running git diff --no-index for decoded text");
+    let _ = fs::remove_dir_all(&scratch);
+    let output = output?;
+
+    if !output.status.success() && output.status.code() != Some(1) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git diff --no-index failed:\n  {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The patch git produces after running the file's textconv driver.
+fn textconv_diff(scope: &DiffScope, entry: &FileDiffEntry, color: bool) -> Result<String> {
+    let color = if color {
+        "--color=always"
+    } else {
+        "--no-color"
+    };
+    let mut args = vec!["diff", "--no-ext-diff", "--textconv", "-M", color];
+    if entry.change_type == ChangeType::Untracked {
+        args.extend(["--no-index", "--", "/dev/null", entry.git_path.as_str()]);
+    } else {
+        args.extend(scope.git_args());
+        args.push("--");
+        args.extend(entry.orig_path.as_deref());
+        args.push(&entry.git_path);
+    }
+
+    let output = StdCommand::new("git")
+        .args(&args)
+        .current_dir(get_repo_root()?)
+        .output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+    if !output.status.success() && output.status.code() != Some(1) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parses `--numstat -z` output into line counts keyed by the (new) path.
+fn parse_numstat(raw: &str) -> HashMap<String, (Option<usize>, Option<usize>)> {
+    let mut counts = HashMap::new();
+    let mut fields = raw.split('\0');
+    while let Some(record) = fields.next() {
+        let mut parts = record.splitn(3, '\t');
+        let (Some(additions), Some(deletions), Some(path)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // Renames leave the path empty and follow with old and new paths.
+        let path = if path.is_empty() {
+            fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+        counts.insert(
+            path.to_string(),
+            (
+                parse_numstat_value(additions),
+                parse_numstat_value(deletions),
+            ),
+        );
+    }
+    counts
+}
+
+fn git_diff_output(scope: &DiffScope, format: &str, pathspecs: &[String]) -> Result<String> {
+    let mut args = vec!["diff", "--no-ext-diff", "-M", "-z", format];
+    args.extend(scope.git_args());
+    if !pathspecs.is_empty() {
+        args.push("--");
+        args.extend(pathspecs.iter().map(String::as_str));
     }
-    args.push("--");
-    args.push(path);
 
     let output = StdCommand::new("git")
         .args(&args)
@@ -167,16 +911,29 @@ fn get_line_change_counts(
         let stderr = String::from_utf8_lossy(&output.stderr);
         bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
     }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if let Some(line) = stdout.lines().next() {
-        let mut parts = line.split('\t');
-        let additions = parts.next().and_then(parse_numstat_value);
-        let deletions = parts.next().and_then(parse_numstat_value);
-        return Ok((additions, deletions));
+fn classify_change(status: &StatusEntry, staged: bool) -> Option<ChangeType> {
+    if status.is_untracked() {
+        return (!staged).then_some(ChangeType::Untracked);
     }
 
-    Ok((Some(0), Some(0)))
+    let state = if staged {
+        status.index
+    } else {
+        status.worktree
+    };
+    match state {
+        FileState::Modified => Some(ChangeType::Modified),
+        FileState::Added => Some(ChangeType::Created),
+        FileState::Deleted => Some(ChangeType::Deleted),
+        FileState::Renamed => Some(ChangeType::Renamed),
+        FileState::Copied => Some(ChangeType::Copied),
+        FileState::TypeChanged => Some(ChangeType::TypeChanged),
+        FileState::Unmerged => Some(ChangeType::Unmerged),
+        FileState::Unmodified | FileState::Untracked => None,
+    }
 }
 
 fn parse_numstat_value(raw: &str) -> Option<usize> {
@@ -187,8 +944,42 @@ fn parse_numstat_value(raw: &str) -> Option<usize> {
     }
 }
 
-fn count_file_lines(path: &str) -> Result<usize> {
-    let bytes = fs::read(path).with_context(|| format!("reading {}", path))?;
+/// Counts lines of untracked files on a few threads; `None` for binary or unreadable ones.
+fn count_untracked_lines(root: &Path, paths: &[&str]) -> Result<Vec<Option<usize>>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let workers = std::thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .min(paths.len());
+    let chunk_size = paths.len().div_ceil(workers);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|path| count_file_lines(&root.join(path)).ok())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut counts = Vec::with_capacity(paths.len());
+        for handle in handles {
+            match handle.join() {
+                Ok(chunk) => counts.extend(chunk),
+                Err(_) => bail!("counting lines of untracked files failed"),
+            }
+        }
+        Ok(counts)
+    })
+}
+
+fn count_file_lines(path: &Path) -> Result<usize> {
+    let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
     if bytes.contains(&0) {
         bail!("binary file");
     }
@@ -196,10 +987,19 @@ fn count_file_lines(path: &str) -> Result<usize> {
     Ok(text.lines().count())
 }
 
-fn format_selector_item(entry: &FileDiffEntry) -> String {
-    let stat_text = match (entry.additions, entry.deletions) {
-        (Some(add), Some(del)) => format!("+{} -{}", add, del),
-        _ => "binary".to_string(),
+pub fn format_selector_item(entry: &FileDiffEntry) -> String {
+    let counts = match (entry.additions, entry.deletions) {
+        (Some(add), Some(del)) => Some(format!("+{} -{}", add, del)),
+        _ => None,
+    };
+    let stat_text = match (&entry.binary, counts) {
+        (Some(binary), Some(counts)) => match &binary.textconv {
+            Some(driver) => format!("{}, via textconv '{}'", counts, driver),
+            None => format!("{}, {}", counts, binary.encoding.unwrap_or(binary.mime)),
+        },
+        (Some(binary), None) => format!("binary, {}", binary.summary()),
+        (None, Some(counts)) => counts,
+        (None, None) => "binary".to_string(),
     };
     format!(
         "{} [{}] ({})",
@@ -209,25 +1009,91 @@ fn format_selector_item(entry: &FileDiffEntry) -> String {
     )
 }
 
-fn show_diff_for_path(path: &str, staged: bool, untracked: bool) -> Result<()> {
-    if untracked {
-        show_untracked_diff(path)?;
-    } else {
-        show_tracked_diff(path, staged)?;
+/// Prints the patch for one entry, passing the old path of a rename too so
+/// git can pair the two sides.
+pub fn show_diff_for_entry(
+    scope: &DiffScope,
+    entry: &FileDiffEntry,
+    render: RenderOptions,
+) -> Result<()> {
+    if let Some(binary) = &entry.binary {
+        return show_binary_diff(scope, entry, binary, render);
     }
-    Ok(())
+    // Entry paths are relative to the repository root, wherever SupGIT runs.
+    let repo_root = get_repo_root()?;
+    let root = Path::new(&repo_root);
+    if entry.change_type == ChangeType::Untracked {
+        return show_untracked_diff(&entry.git_path, root, render);
+    }
+    let mut paths: Vec<&str> = entry.orig_path.as_deref().into_iter().collect();
+    paths.push(&entry.git_path);
+    show_tracked_diff(scope, &paths, root, render)
+}
+
+/// Shows a binary file's text form when it has one, otherwise a summary.
+fn show_binary_diff(
+    scope: &DiffScope,
+    entry: &FileDiffEntry,
+    binary: &BinaryDetails,
+    render: RenderOptions,
+) -> Result<()> {
+    let color = !render.is_custom();
+    let patch = if binary.textconv.is_some() {
+        textconv_diff(scope, entry, color)?
+    } else if binary.encoding.is_some() {
+        let repo_root = get_repo_root()?;
+        let (old, new) = entry_sides(scope, entry, Path::new(&repo_root));
+        decoded_diff_for_sides(&entry.git_path, &old, &new, color)?.unwrap_or_default()
+    } else {
+        println!("Binary file {} ({})", entry.display_path, binary.mime);
+        if let Some(size) = binary.size_change() {
+            let note = match (binary.old_size, binary.new_size) {
+                (Some(_), None) => " (deleted)",
+                (None, Some(_)) => " (new)",
+                _ => "",
+            };
+            println!("  size:       {}{}", size, note);
+        }
+        if let Some(dimensions) = binary.dimensions_change() {
+            println!("  dimensions: {}", dimensions);
+        }
+        return Ok(());
+    };
+    print_diff_output(&patch, &entry.display_path, render)
 }
 
-fn show_tracked_diff(path: &str, staged: bool) -> Result<()> {
-    let mut args = vec!["-c", "color.ui=always", "diff"];
-    if staged {
-        args.push("--staged");
+/// Pages git's colored diff, or uncolored output run through SupGIT's
+/// renderer when a rendering option is set.
+fn print_diff_output(stdout: &str, label: &str, render: RenderOptions) -> Result<()> {
+    if stdout.trim().is_empty() {
+        println!("No diff output for '{}'.", label);
+        Ok(())
+    } else if render.is_custom() {
+        pager::page(&render_diff(stdout, render))
+    } else {
+        pager::page(stdout)
     }
+}
+
+fn show_tracked_diff(
+    scope: &DiffScope,
+    paths: &[&str],
+    dir: &Path,
+    render: RenderOptions,
+) -> Result<()> {
+    let color = if render.is_custom() {
+        "color.ui=never"
+    } else {
+        "color.ui=always"
+    };
+    let mut args = vec!["-c", color, "diff", "--no-ext-diff", "-M"];
+    args.extend(scope.git_args());
     args.push("--");
-    args.push(path);
+    args.extend_from_slice(paths);
 
     let output = StdCommand::new("git")
         .args(&args)
+        .current_dir(dir)
         .output()
         .with_context(|| format!("running git {}", args.join(" ")))?;
 
@@ -237,17 +1103,11 @@ fn show_tracked_diff(path: &str, staged: bool) -> Result<()> {
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    if stdout.trim().is_empty() {
-        println!("No diff output for '{}'.", path);
-    } else {
-        print!("{}", stdout);
-    }
-
-    Ok(())
+    print_diff_output(&stdout, &paths.join("' / '"), render)
 }
 
-fn show_untracked_diff(path: &str) -> Result<()> {
-    if !Path::new(path).exists() {
+fn show_untracked_diff(path: &str, dir: &Path, render: RenderOptions) -> Result<()> {
+    if !dir.join(path).exists() {
         bail!("file '{}' no longer exists", path);
     }
 
@@ -255,11 +1115,16 @@ fn show_untracked_diff(path: &str) -> Result<()> {
         .args([
             "diff",
             "--no-index",
-            "--color=always",
+            if render.is_custom() {
+                "--no-color"
+            } else {
+                "--color=always"
+            },
             "--",
             "/dev/null",
             path,
         ])
+        .current_dir(dir)
         .output()
         .context("running git diff --no-index for untracked file")?;
 
@@ -269,10 +1134,5 @@ fn show_untracked_diff(path: &str) -> Result<()> {
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    if stdout.trim().is_empty() {
-        println!("No diff output for '{}'.", path);
-    } else {
-        print!("{}", stdout);
-    }
-    Ok(())
+    print_diff_output(&stdout, path, render)
 }
@@ -0,0 +1,121 @@
+use std::process::Command as StdCommand;
+
+use anyhow::{Context, Result, bail};
+use dialoguer::Select;
+
+use crate::git::{git_stdout, run_git_quiet, run_git_silent};
+use crate::journal;
+use crate::scan::{self, scan_staged};
+use crate::status::{
+    CommitSummary, get_in_progress_operation, get_staged_files, get_unpushed_commits,
+    invalidate_porcelain_cache,
+};
+
+const MAX_CANDIDATES: usize = 20;
+
+pub fn run_fixup(commit: Option<String>, allow_secrets: bool) -> Result<()> {
+    if let Some(operation) = get_in_progress_operation()? {
+        bail!(
+            "a {} is in progress; finish or abort it before running fixup",
+            operation.label()
+        );
+    }
+    if get_staged_files()?.is_empty() {
+        bail!("nothing staged; stage the changes to fold in with 'supgit stage' first");
+    }
+
+    let candidates = get_unpushed_commits(MAX_CANDIDATES)?;
+    if candidates.is_empty() {
+        bail!("no unpushed commits to fix up; published commits can't be rewritten safely");
+    }
+
+    let target = match commit {
+        Some(rev) => find_candidate(candidates, &rev)?,
+        None => pick_candidate(candidates)?,
+    };
+
+    let merges = git_stdout(&["rev-list", "--merges", &format!("{}..HEAD", target.hash)]);
+    if merges.is_some() {
+        bail!(
+            "there are merge commits after {}; fixup only works on linear history",
+            target.short_hash
+        );
+    }
+
+    scan::enforce(&scan_staged()?, allow_secrets, "fixup")?;
+
+    journal::record(&format!("fixup {}", target.short_hash))?;
+    run_git_quiet(&["commit", "--fixup", &target.hash])?;
+    println!("→ Created fixup! commit for {}", target.short_hash);
+
+    let is_root = git_stdout(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^", target.hash),
+    ])
+    .is_none();
+    let base = format!("{}^", target.hash);
+    let mut args = vec!["rebase", "-i", "--autosquash", "--autostash"];
+    if is_root {
+        args.push("--root");
+    } else {
+        args.push(&base);
+    }
+
+    println!("→ Folding it into '{}'...", target.subject);
+    let output = StdCommand::new("git")
+        .args(&args)
+        .env("GIT_SEQUENCE_EDITOR", "true")
+        .output()
+        .context("running git rebase --autosquash")?;
+    invalidate_porcelain_cache();
+
+    if !output.status.success() {
+        let _ = run_git_silent(&["rebase", "--abort"]);
+        run_git_silent(&["reset", "--soft", "HEAD~1"])?;
+        eprintln!(
+            "✗ The changes conflict with commits made after {}",
+            target.short_hash
+        );
+        eprintln!("  The rebase was aborted and your changes are staged again.");
+        eprintln!("  Commit them normally instead, or fix up a more recent commit.");
+        bail!("fixup of {} aborted due to conflicts", target.short_hash);
+    }
+
+    println!("✓ Folded staged changes into '{}'", target.subject);
+    Ok(())
+}
+
+fn find_candidate(candidates: Vec<CommitSummary>, rev: &str) -> Result<CommitSummary> {
+    let Some(hash) = git_stdout(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", rev),
+    ]) else {
+        bail!("'{}' is not a commit", rev);
+    };
+    candidates
+        .into_iter()
+        .find(|c| c.hash == hash)
+        .with_context(|| {
+            format!(
+                "'{}' is not one of the last {} unpushed commits",
+                rev, MAX_CANDIDATES
+            )
+        })
+}
+
+fn pick_candidate(mut candidates: Vec<CommitSummary>) -> Result<CommitSummary> {
+    let items: Vec<String> = candidates
+        .iter()
+        .map(|c| format!("{} {} ({})", c.short_hash, c.subject, c.age))
+        .collect();
+    let selection = Select::new()
+        .with_prompt("Fold the staged changes into which commit?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(candidates.swap_remove(selection))
+}
@@ -0,0 +1,382 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use anyhow::{Context, Result, bail};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::git::{git_stdout, run_git_silent};
+use crate::journal;
+use crate::status::{
+    CommitSummary, get_git_dir, get_in_progress_operation, get_published_refs, get_recent_commits,
+    invalidate_porcelain_cache,
+};
+
+const DEFAULT_COUNT: usize = 10;
+const WORK_DIR: &str = "supgit/history";
+
+#[derive(Clone, PartialEq)]
+enum Action {
+    Pick,
+    Reword(String),
+    Squash,
+    Drop,
+    Split,
+}
+
+impl Action {
+    fn label(&self) -> &'static str {
+        match self {
+            Action::Pick => "pick",
+            Action::Reword(_) => "reword",
+            Action::Squash => "squash",
+            Action::Drop => "drop",
+            Action::Split => "split",
+        }
+    }
+}
+
+struct PlanItem {
+    commit: CommitSummary,
+    action: Action,
+}
+
+impl PlanItem {
+    fn describe(&self) -> String {
+        let subject = match self.action {
+            Action::Reword(ref message) => message.lines().next().unwrap_or_default(),
+            _ => self.commit.subject.as_str(),
+        };
+        format!(
+            "{:<6} {} {} ({})",
+            self.action.label(),
+            self.commit.short_hash,
+            subject,
+            self.commit.age
+        )
+    }
+}
+
+pub fn run_history_edit(count: Option<usize>, force: bool) -> Result<()> {
+    if let Some(operation) = get_in_progress_operation()? {
+        bail!(
+            "a {} is in progress; finish or abort it before editing history",
+            operation.label()
+        );
+    }
+
+    let commits = get_recent_commits(count.unwrap_or(DEFAULT_COUNT), !force)?;
+    let Some(oldest) = commits.last() else {
+        if force {
+            bail!("there are no commits to edit");
+        }
+        bail!("no unpushed commits to edit; pass --force to rewrite published history");
+    };
+
+    let base = git_stdout(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^", oldest.hash),
+    ]);
+    let range = match base {
+        Some(ref base) => format!("{}..HEAD", base),
+        None => "HEAD".to_string(),
+    };
+    if git_stdout(&["rev-list", "--merges", &range]).is_some() {
+        bail!("the commits to edit include merges; history edit only works on linear history");
+    }
+
+    let published = get_published_refs(&oldest.hash)?;
+    if !published.is_empty() {
+        eprintln!(
+            "⚠ Some of these commits are already on {}.",
+            published.join(", ")
+        );
+        eprintln!("  Rewriting them means force-pushing and can disrupt collaborators.");
+        let confirm = Confirm::new()
+            .with_prompt("Edit published history anyway?")
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let mut plan: Vec<PlanItem> = commits
+        .into_iter()
+        .map(|commit| PlanItem {
+            commit,
+            action: Action::Pick,
+        })
+        .collect();
+    let original_order: Vec<String> = plan.iter().map(|i| i.commit.hash.clone()).collect();
+
+    let mut cursor = 0;
+    loop {
+        let mut items: Vec<String> = plan.iter().map(PlanItem::describe).collect();
+        items.push("Apply these changes".to_string());
+        items.push("Cancel".to_string());
+
+        let selection = Select::new()
+            .with_prompt("Pick a commit to change (newest first)")
+            .items(&items)
+            .default(cursor)
+            .interact()?;
+
+        if selection == plan.len() {
+            break;
+        }
+        if selection == plan.len() + 1 {
+            println!("Cancelled.");
+            return Ok(());
+        }
+        cursor = edit_item(&mut plan, selection)?;
+    }
+
+    let reordered = plan
+        .iter()
+        .map(|i| &i.commit.hash)
+        .ne(original_order.iter());
+    if !reordered && plan.iter().all(|i| i.action == Action::Pick) {
+        println!("Nothing to change.");
+        return Ok(());
+    }
+    if plan.iter().all(|i| i.action == Action::Drop) {
+        bail!("every commit is marked to drop; keep at least one or use 'supgit reset' instead");
+    }
+    if let Some(first) = plan.iter().rev().find(|i| i.action != Action::Drop)
+        && first.action == Action::Squash
+    {
+        bail!(
+            "{} is the oldest remaining commit, so there is nothing to squash it into",
+            first.commit.short_hash
+        );
+    }
+
+    apply_plan(&plan, base.as_deref(), !published.is_empty())
+}
+
+/// Edits one commit's action or position and returns the new cursor index.
+fn edit_item(plan: &mut [PlanItem], idx: usize) -> Result<usize> {
+    let options = [
+        "Keep as is",
+        "Reword the message",
+        "Squash into the older commit below",
+        "Drop this commit",
+        "Split into one commit per file",
+        "Move up (make it newer)",
+        "Move down (make it older)",
+        "Back",
+    ];
+    let choice = Select::new()
+        .with_prompt(format!(
+            "{} {}",
+            plan[idx].commit.short_hash, plan[idx].commit.subject
+        ))
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    match choice {
+        0 => plan[idx].action = Action::Pick,
+        1 => {
+            let current = git_stdout(&["log", "-1", "--format=%B", &plan[idx].commit.hash])
+                .unwrap_or_else(|| plan[idx].commit.subject.clone());
+            let (subject, body) = current.split_once('\n').unwrap_or((&current, ""));
+            let new_subject: String = Input::new()
+                .with_prompt("New subject")
+                .with_initial_text(subject)
+                .interact_text()?;
+            let mut message = new_subject.trim().to_string();
+            if message.is_empty() {
+                bail!("commit message cannot be empty");
+            }
+            if !body.trim().is_empty() {
+                message.push_str("\n\n");
+                message.push_str(body.trim());
+            }
+            plan[idx].action = Action::Reword(message);
+        }
+        2 => {
+            if idx + 1 == plan.len() {
+                println!("⚠ The oldest commit in the list has nothing below it to squash into.");
+            } else {
+                plan[idx].action = Action::Squash;
+            }
+        }
+        3 => plan[idx].action = Action::Drop,
+        4 => {
+            let files = changed_files(&plan[idx].commit.hash)?;
+            let has_parent = git_stdout(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^", plan[idx].commit.hash),
+            ])
+            .is_some();
+            if !has_parent {
+                println!("⚠ The first commit of the repository can't be split.");
+            } else if files.len() < 2 {
+                println!("⚠ This commit only touches one file, so there is nothing to split.");
+            } else {
+                plan[idx].action = Action::Split;
+            }
+        }
+        5 if idx > 0 => {
+            plan.swap(idx, idx - 1);
+            return Ok(idx - 1);
+        }
+        6 if idx + 1 < plan.len() => {
+            plan.swap(idx, idx + 1);
+            return Ok(idx + 1);
+        }
+        _ => {}
+    }
+    Ok(idx)
+}
+
+fn changed_files(hash: &str) -> Result<Vec<String>> {
+    let output = StdCommand::new("git")
+        .args([
+            "diff-tree",
+            "--no-commit-id",
+            "--name-only",
+            "--no-renames",
+            "-r",
+            "-z",
+            hash,
+        ])
+        .output()
+        .context("running git diff-tree")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git diff-tree failed:\n  {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Writes the rebase todo (oldest first) plus any message files, then runs
+/// `git rebase -i` with a sequence editor that just copies the todo in.
+fn apply_plan(plan: &[PlanItem], base: Option<&str>, published: bool) -> Result<()> {
+    let work_dir = get_git_dir()?.join(WORK_DIR);
+    if work_dir.exists() {
+        fs::remove_dir_all(&work_dir)
+            .with_context(|| format!("clearing {}", work_dir.display()))?;
+    }
+    fs::create_dir_all(&work_dir).with_context(|| format!("creating {}", work_dir.display()))?;
+
+    let mut todo = String::new();
+    for (n, item) in plan.iter().rev().enumerate() {
+        let hash = &item.commit.hash;
+        match item.action {
+            Action::Pick => todo.push_str(&format!("pick {}\n", hash)),
+            Action::Drop => todo.push_str(&format!("drop {}\n", hash)),
+            Action::Squash => todo.push_str(&format!("squash {}\n", hash)),
+            Action::Reword(ref message) => {
+                let file = write_message(&work_dir, &format!("reword-{}", n), message)?;
+                todo.push_str(&format!("pick {}\n", hash));
+                todo.push_str(&format!(
+                    "exec git commit --amend --only --quiet --file={}\n",
+                    shell_quote(&file)
+                ));
+            }
+            Action::Split => {
+                let files = changed_files(hash)?;
+                let mut script = String::from("git reset --quiet HEAD~1");
+                for (part, file) in files.iter().enumerate() {
+                    let message = format!(
+                        "{} ({}/{})\n\nSplit from {}; contains {}.",
+                        item.commit.subject,
+                        part + 1,
+                        files.len(),
+                        item.commit.short_hash,
+                        file
+                    );
+                    let message_file =
+                        write_message(&work_dir, &format!("split-{}-{}", n, part), &message)?;
+                    script.push_str(&format!(
+                        " && git add -A -- {} && git commit --quiet --file={}",
+                        shell_quote(file),
+                        shell_quote(&message_file)
+                    ));
+                }
+                todo.push_str(&format!("pick {}\n", hash));
+                todo.push_str(&format!("exec {}\n", script));
+            }
+        }
+    }
+
+    let todo_path = work_dir.join("todo");
+    fs::write(&todo_path, &todo).with_context(|| format!("writing {}", todo_path.display()))?;
+
+    journal::record("history edit")?;
+    let mut args = vec!["rebase", "-i", "--autostash"];
+    match base {
+        Some(base) => args.push(base),
+        None => args.push("--root"),
+    }
+
+    println!("→ Rewriting {} commit(s)...", plan.len());
+    let output = StdCommand::new("git")
+        .args(&args)
+        .env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+        )
+        .env("GIT_EDITOR", "true")
+        .output()
+        .context("running git rebase -i")?;
+    invalidate_porcelain_cache();
+    let _ = fs::remove_dir_all(&work_dir);
+
+    if !output.status.success() {
+        let _ = run_git_silent(&["rebase", "--abort"]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = stderr
+            .lines()
+            .chain(stdout.lines())
+            .find(|line| line.contains("CONFLICT") || line.starts_with("error"))
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        eprintln!("✗ The new history doesn't apply cleanly, so nothing was changed.");
+        if !detail.is_empty() {
+            eprintln!("  {}", detail);
+        }
+        eprintln!(
+            "  Try a smaller change, e.g. avoid reordering commits that touch the same lines."
+        );
+        bail!("history edit aborted");
+    }
+
+    let count = |label: &str| plan.iter().filter(|i| i.action.label() == label).count();
+    println!(
+        "✓ History rewritten ({} reworded, {} squashed, {} dropped, {} split)",
+        count("reword"),
+        count("squash"),
+        count("drop"),
+        count("split")
+    );
+    if published {
+        println!("→ Update the remote with 'git push --force-with-lease'.");
+    }
+    Ok(())
+}
+
+fn write_message(dir: &Path, name: &str, message: &str) -> Result<String> {
+    let path = dir.join(name);
+    fs::write(&path, format!("{}\n", message))
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,426 @@
+use std::io::IsTerminal;
+use std::process::Command as StdCommand;
+
+use anyhow::{Context, Result, bail};
+use dialoguer::Select;
+use serde::Serialize;
+
+use super::show::browse_commit;
+use crate::json::print_json;
+use crate::pager;
+
+pub struct LogOptions {
+    pub short: bool,
+    pub limit: Option<usize>,
+    pub author: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub path: Option<String>,
+    pub grep: Option<String>,
+    pub graph: bool,
+    pub interactive: bool,
+}
+
+#[derive(Serialize)]
+struct LogEntry {
+    hash: String,
+    #[serde(skip)]
+    short_hash: String,
+    #[serde(skip)]
+    parents: Vec<String>,
+    author: String,
+    email: String,
+    date: String,
+    #[serde(skip)]
+    age: String,
+    #[serde(skip)]
+    refs: String,
+    subject: String,
+    files_changed: usize,
+    insertions: usize,
+    deletions: usize,
+}
+
+impl LogEntry {
+    fn stat_text(&self) -> String {
+        format!(
+            "{} file(s), +{} -{}",
+            self.files_changed, self.insertions, self.deletions
+        )
+    }
+}
+
+struct Palette {
+    hash: &'static str,
+    refs: &'static str,
+    dim: &'static str,
+    reset: &'static str,
+}
+
+impl Palette {
+    fn detect() -> Self {
+        if std::io::stdout().is_terminal() {
+            Palette {
+                hash: "\x1b[33m",
+                refs: "\x1b[32m",
+                dim: "\x1b[2m",
+                reset: "\x1b[0m",
+            }
+        } else {
+            Palette {
+                hash: "",
+                refs: "",
+                dim: "",
+                reset: "",
+            }
+        }
+    }
+}
+
+pub fn run_log(options: LogOptions, json: bool) -> Result<()> {
+    // git only rewrites parents for path limiting, so with these filters the
+    // graph would open lanes for commits that are never shown.
+    if options.graph && (options.author.is_some() || options.grep.is_some()) {
+        bail!("--graph can't be combined with --author or --grep; drop --graph to filter commits");
+    }
+    let limit = options.limit.unwrap_or(if options.short || options.graph {
+        20
+    } else {
+        40
+    });
+    let entries = get_log_entries(&options, limit)?;
+
+    if json {
+        return print_json(&entries);
+    }
+    if entries.is_empty() {
+        println!("No commits match.");
+        return Ok(());
+    }
+    if options.interactive {
+        return run_log_interactive(&entries);
+    }
+
+    let palette = Palette::detect();
+    let mut out = String::new();
+    if options.graph {
+        render_graph(&mut out, &entries, &palette);
+    } else if options.short {
+        for entry in &entries {
+            out.push_str(&format_short(entry, &palette));
+            out.push('\n');
+        }
+    } else {
+        for entry in &entries {
+            render_full(&mut out, entry, &palette);
+        }
+    }
+    pager::page(&out)
+}
+
+fn get_log_entries(options: &LogOptions, limit: usize) -> Result<Vec<LogEntry>> {
+    let mut args = vec![
+        "log".to_string(),
+        format!("-n{}", limit),
+        "--format=%x1e%H%x00%h%x00%P%x00%an%x00%ae%x00%aI%x00%ar%x00%D%x00%s".to_string(),
+        "--shortstat".to_string(),
+    ];
+    if options.graph {
+        // With --parents, %P lists the parents within the filtered history
+        // (e.g. the previous commit touching --path) instead of the real ones.
+        args.push("--topo-order".to_string());
+        args.push("--parents".to_string());
+    }
+    if let Some(ref author) = options.author {
+        args.push(format!("--author={}", author));
+    }
+    if let Some(ref since) = options.since {
+        args.push(format!("--since={}", since));
+    }
+    if let Some(ref until) = options.until {
+        args.push(format!("--until={}", until));
+    }
+    if let Some(ref grep) = options.grep {
+        args.push(format!("--grep={}", grep));
+        args.push("--regexp-ignore-case".to_string());
+    }
+    if let Some(ref path) = options.path {
+        args.push("--".to_string());
+        args.push(path.clone());
+    }
+
+    let output = StdCommand::new("git")
+        .args(&args)
+        .output()
+        .context("running git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("does not have any commits") {
+            return Ok(Vec::new());
+        }
+        bail!("git log failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let entries = stdout
+        .split('\x1e')
+        .filter_map(|record| {
+            let (header, stat) = record.split_once('\n').unwrap_or((record, ""));
+            let mut fields = header.split('\0');
+            let mut entry = LogEntry {
+                hash: fields.next().filter(|s| !s.is_empty())?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                parents: fields
+                    .next()?
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect(),
+                author: fields.next()?.to_string(),
+                email: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                age: fields.next()?.to_string(),
+                refs: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                files_changed: 0,
+                insertions: 0,
+                deletions: 0,
+            };
+            parse_shortstat(stat, &mut entry);
+            Some(entry)
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Reads " 2 files changed, 5 insertions(+), 1 deletion(-)".
+fn parse_shortstat(stat: &str, entry: &mut LogEntry) {
+    for part in stat.trim().split(", ") {
+        let Some((count, label)) = part.trim().split_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse() else {
+            continue;
+        };
+        if label.starts_with("file") {
+            entry.files_changed = count;
+        } else if label.starts_with("insertion") {
+            entry.insertions = count;
+        } else if label.starts_with("deletion") {
+            entry.deletions = count;
+        }
+    }
+}
+
+fn format_short(entry: &LogEntry, palette: &Palette) -> String {
+    let refs = if entry.refs.is_empty() {
+        String::new()
+    } else {
+        format!(" {}({}){}", palette.refs, entry.refs, palette.reset)
+    };
+    format!(
+        "{}{}{}{} {} {}({}, {}; +{} -{}){}",
+        palette.hash,
+        entry.short_hash,
+        palette.reset,
+        refs,
+        entry.subject,
+        palette.dim,
+        entry.author,
+        entry.age,
+        entry.insertions,
+        entry.deletions,
+        palette.reset
+    )
+}
+
+fn render_full(out: &mut String, entry: &LogEntry, palette: &Palette) {
+    let refs = if entry.refs.is_empty() {
+        String::new()
+    } else {
+        format!(" {}({}){}", palette.refs, entry.refs, palette.reset)
+    };
+    out.push_str(&format!(
+        "{}commit {}{}{}\n",
+        palette.hash, entry.hash, palette.reset, refs
+    ));
+    out.push_str(&format!("Author: {} <{}>\n", entry.author, entry.email));
+    out.push_str(&format!("Date:   {} ({})\n", entry.age, entry.date));
+    out.push('\n');
+    out.push_str(&format!("    {}\n", entry.subject));
+    out.push('\n');
+    out.push_str(&format!(
+        "    {}{}{}\n",
+        palette.dim,
+        entry.stat_text(),
+        palette.reset
+    ));
+    out.push('\n');
+}
+
+/// Draws history lanes: `*` a commit, `|` a lane, `\` a merge fork, `/` a join.
+fn render_graph(out: &mut String, entries: &[LogEntry], palette: &Palette) {
+    let mut lanes: Vec<Option<String>> = Vec::new();
+
+    for entry in entries {
+        let matching: Vec<usize> = lanes
+            .iter()
+            .enumerate()
+            .filter(|(_, lane)| lane.as_deref() == Some(entry.hash.as_str()))
+            .map(|(idx, _)| idx)
+            .collect();
+        let col = match matching.first() {
+            Some(&idx) => idx,
+            None => claim_lane(&mut lanes, &entry.hash),
+        };
+
+        // Branches that forked from this commit fold back into its lane.
+        let joining = &matching[matching.len().min(1)..];
+        if !joining.is_empty() {
+            out.push_str(&format!("{}\n", transition_row(&lanes, joining, '/')));
+            for &idx in joining {
+                lanes[idx] = None;
+            }
+            trim_lanes(&mut lanes);
+        }
+
+        let row: String = lanes
+            .iter()
+            .enumerate()
+            .map(|(idx, lane)| match (idx == col, lane) {
+                (true, _) => "* ",
+                (false, Some(_)) => "| ",
+                (false, None) => "  ",
+            })
+            .collect();
+        out.push_str(&format!("{}{}\n", row, format_short(entry, palette)));
+
+        lanes[col] = entry.parents.first().cloned();
+        let mut opened = Vec::new();
+        for parent in entry.parents.iter().skip(1) {
+            if !lanes.iter().any(|lane| lane.as_deref() == Some(parent)) {
+                opened.push(claim_lane(&mut lanes, parent));
+            }
+        }
+        if !opened.is_empty() {
+            out.push_str(&format!("{}\n", transition_row(&lanes, &opened, '\\')));
+        }
+        trim_lanes(&mut lanes);
+    }
+}
+
+fn claim_lane(lanes: &mut Vec<Option<String>>, hash: &str) -> usize {
+    match lanes.iter().position(Option::is_none) {
+        Some(idx) => {
+            lanes[idx] = Some(hash.to_string());
+            idx
+        }
+        None => {
+            lanes.push(Some(hash.to_string()));
+            lanes.len() - 1
+        }
+    }
+}
+
+fn trim_lanes(lanes: &mut Vec<Option<String>>) {
+    while lanes.last().is_some_and(Option::is_none) {
+        lanes.pop();
+    }
+}
+
+/// Renders the row between commits, drawing `edge` just left of each lane in
+/// `moving` and `|` for every other active lane.
+fn transition_row(lanes: &[Option<String>], moving: &[usize], edge: char) -> String {
+    let mut row = vec![' '; lanes.len() * 2];
+    for (idx, lane) in lanes.iter().enumerate() {
+        if moving.contains(&idx) {
+            row[(idx * 2).saturating_sub(1)] = edge;
+        } else if lane.is_some() {
+            row[idx * 2] = '|';
+        }
+    }
+    row.into_iter().collect::<String>().trim_end().to_string()
+}
+
+fn run_log_interactive(entries: &[LogEntry]) -> Result<()> {
+    let palette = Palette {
+        hash: "",
+        refs: "",
+        dim: "",
+        reset: "",
+    };
+    let mut items: Vec<String> = entries.iter().map(|e| format_short(e, &palette)).collect();
+    items.push("Quit".to_string());
+
+    let mut cursor = 0;
+    loop {
+        let selection = Select::new()
+            .with_prompt("Select a commit to view its changes")
+            .items(&items)
+            .default(cursor)
+            .interact()?;
+        if selection == entries.len() {
+            return Ok(());
+        }
+        cursor = selection;
+        browse_commit(&entries[selection].hash)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(stat: &str) -> (usize, usize, usize) {
+        let mut entry = LogEntry {
+            hash: String::new(),
+            short_hash: String::new(),
+            parents: Vec::new(),
+            author: String::new(),
+            email: String::new(),
+            date: String::new(),
+            age: String::new(),
+            refs: String::new(),
+            subject: String::new(),
+            files_changed: 0,
+            insertions: 0,
+            deletions: 0,
+        };
+        parse_shortstat(stat, &mut entry);
+        (entry.files_changed, entry.insertions, entry.deletions)
+    }
+
+    #[test]
+    fn reads_plural_counts() {
+        assert_eq!(
+            counts(" 12 files changed, 340 insertions(+), 27 deletions(-)\n"),
+            (12, 340, 27)
+        );
+    }
+
+    #[test]
+    fn reads_singular_counts() {
+        assert_eq!(
+            counts(" 1 file changed, 1 insertion(+), 1 deletion(-)"),
+            (1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn reads_one_sided_changes() {
+        assert_eq!(counts(" 2 files changed, 5 insertions(+)"), (2, 5, 0));
+        assert_eq!(counts(" 1 file changed, 3 deletions(-)"), (1, 0, 3));
+        // Mode changes and empty files change no lines.
+        assert_eq!(
+            counts(" 1 file changed, 0 insertions(+), 0 deletions(-)"),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn ignores_empty_and_unexpected_text() {
+        assert_eq!(counts(""), (0, 0, 0));
+        assert_eq!(counts("not a stat line"), (0, 0, 0));
+    }
+}
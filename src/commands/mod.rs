@@ -3,19 +3,35 @@ mod branch;
 mod clone;
 mod commit;
 mod diff;
+mod fixup;
+mod history;
+mod log;
 mod reset;
+mod restore_backup;
+mod show;
 mod stage;
+mod stash;
+mod status;
 mod sync;
+mod undo;
 mod unstage;
 mod update;
 
 pub use alias::{run_alias, run_unalias};
-pub use branch::{create_branch, delete_branch, run_branch_interactive};
+pub use branch::{create_branch, delete_branch, print_branches_json, run_branch_interactive};
 pub use clone::run_clone;
-pub use commit::run_commit;
-pub use diff::run_diff;
+pub use commit::{CommitOptions, run_commit};
+pub use diff::{DiffOptions, run_diff};
+pub use fixup::run_fixup;
+pub use history::run_history_edit;
+pub use log::{LogOptions, run_log};
 pub use reset::run_reset;
+pub use restore_backup::run_restore_backup;
+pub use show::run_show;
 pub use stage::stage_targets;
+pub use stash::{StashOptions, run_stash};
+pub use status::run_status;
 pub use sync::{run_pull, run_push, run_sync};
+pub use undo::run_undo;
 pub use unstage::restore_stage;
 pub use update::{check_and_auto_update, run_self_update};
@@ -1,10 +1,13 @@
 use anyhow::Result;
 use dialoguer::{MultiSelect, Select};
 
-use crate::git::run_git_silent;
+use crate::backup::create_backup;
+use crate::git::{run_git_in_dir_silent, run_git_silent};
+use crate::hunks::{HunkAction, run_hunk_selection};
+use crate::journal;
 use crate::status::{
-    get_all_uncommitted_files, get_porcelain_lines, get_repo_root, get_staged_files,
-    get_unstaged_files, get_untracked_files,
+    StatusEntry, get_repo_root, get_staged_files, get_status_entries, get_unstaged_files,
+    get_untracked_files,
 };
 
 pub fn run_reset(
@@ -13,8 +16,9 @@ pub fn run_reset(
     unstaged: bool,
     tracked: bool,
     untracked: bool,
+    patch: bool,
 ) -> Result<()> {
-    let is_interactive = !all && !staged && !unstaged && !tracked && !untracked;
+    let is_interactive = !all && !staged && !unstaged && !tracked && !untracked && !patch;
 
     if is_interactive {
         let selection = Select::new()
@@ -26,6 +30,7 @@ pub fn run_reset(
                 "Tracked files only",
                 "Untracked files only",
                 "Custom files",
+                "Discard hunks...",
             ])
             .default(0)
             .interact()?;
@@ -37,6 +42,7 @@ pub fn run_reset(
             3 => reset_tracked()?,
             4 => reset_untracked()?,
             5 => reset_custom()?,
+            6 => reset_hunks()?,
             _ => {}
         }
     } else if all {
@@ -49,12 +55,26 @@ pub fn run_reset(
         reset_tracked()?;
     } else if untracked {
         reset_untracked()?;
+    } else if patch {
+        reset_hunks()?;
     }
 
     Ok(())
 }
 
+fn backup_before(reason: &str) -> Result<()> {
+    if let Some(id) = create_backup(reason)? {
+        println!(
+            "→ Saved backup {} (restore with 'supgit restore-backup {}')",
+            id, id
+        );
+    }
+    Ok(())
+}
+
 fn reset_all() -> Result<()> {
+    backup_before("reset --all")?;
+    journal::record("reset --all")?;
     run_git_silent(&["reset", "--hard"])?;
     run_git_silent(&["clean", "-fd"])?;
     println!("✓ All files reset.");
@@ -67,6 +87,7 @@ fn reset_staged() -> Result<()> {
         println!("No staged files to reset.");
         return Ok(());
     }
+    journal::record("reset --staged")?;
     run_git_silent(&["restore", "--staged", "."])?;
     println!("✓ Staged files reset.");
     Ok(())
@@ -78,12 +99,15 @@ fn reset_unstaged() -> Result<()> {
         println!("No unstaged changes to reset.");
         return Ok(());
     }
+    journal::record("reset --unstaged")?;
     run_git_silent(&["restore", "."])?;
     println!("✓ Unstaged changes reset.");
     Ok(())
 }
 
 fn reset_tracked() -> Result<()> {
+    backup_before("reset --tracked")?;
+    journal::record("reset --tracked")?;
     run_git_silent(&["reset", "--hard"])?;
     println!("✓ Tracked files reset.");
     Ok(())
@@ -95,21 +119,24 @@ fn reset_untracked() -> Result<()> {
         println!("No untracked files to reset.");
         return Ok(());
     }
+    backup_before("reset --untracked")?;
+    journal::record("reset --untracked")?;
     run_git_silent(&["clean", "-fd"])?;
     println!("✓ Untracked files removed.");
     Ok(())
 }
 
 fn reset_custom() -> Result<()> {
-    let files = get_all_uncommitted_files()?;
-    if files.is_empty() {
+    let entries = get_status_entries()?;
+    if entries.is_empty() {
         println!("No files to reset.");
         return Ok(());
     }
 
+    let items: Vec<String> = entries.iter().map(StatusEntry::display_path).collect();
     let selected = MultiSelect::new()
         .with_prompt("Select files to reset")
-        .items(&files)
+        .items(&items)
         .interact()?;
 
     if selected.is_empty() {
@@ -117,31 +144,63 @@ fn reset_custom() -> Result<()> {
         return Ok(());
     }
 
+    let chosen: Vec<&StatusEntry> = selected.iter().map(|&idx| &entries[idx]).collect();
+    discard_entries(&chosen, false)?;
+    println!("✓ Selected files reset.");
+    Ok(())
+}
+
+/// Discards changes to `entries` after a backup; `keep_staged` resets only the worktree.
+pub fn discard_entries(entries: &[&StatusEntry], keep_staged: bool) -> Result<()> {
+    backup_before("reset")?;
+    journal::record("reset")?;
     let repo_root = get_repo_root()?;
-    for idx in selected {
-        let file = &files[idx];
-        let entries = get_porcelain_lines()?;
-        let status = entries
-            .iter()
-            .find(|(_, p)| p == file)
-            .map(|(s, _)| s.clone())
-            .unwrap_or_default();
-        let xy: Vec<char> = status.chars().collect();
-        let x = xy.first().copied().unwrap_or(' ');
-        let y = xy.get(1).copied().unwrap_or(' ');
-
-        if x == '?' && y == '?' {
-            crate::git::run_git_in_dir_silent(&["clean", "-f", file], &repo_root)?;
-        } else {
-            if x != ' ' {
-                crate::git::run_git_in_dir_silent(&["restore", "--staged", file], &repo_root)?;
-            }
-            if y != ' ' && y != '?' {
-                crate::git::run_git_in_dir_silent(&["restore", file], &repo_root)?;
+    for entry in entries {
+        let path = entry.path.as_str();
+
+        if entry.is_untracked() {
+            run_git_in_dir_silent(&["clean", "-f", "--", path], &repo_root)?;
+            continue;
+        }
+        if entry.is_conflicted() {
+            run_git_in_dir_silent(
+                &[
+                    "restore",
+                    "--source=HEAD",
+                    "--staged",
+                    "--worktree",
+                    "--",
+                    path,
+                ],
+                &repo_root,
+            )?;
+            continue;
+        }
+        if entry.is_staged() && !keep_staged {
+            let mut args = vec!["restore", "--staged", "--", path];
+            args.extend(entry.orig_path.as_deref());
+            run_git_in_dir_silent(&args, &repo_root)?;
+            if let Some(ref orig) = entry.orig_path {
+                run_git_in_dir_silent(&["restore", "--", orig], &repo_root)?;
             }
         }
+        if entry.is_unstaged() {
+            run_git_in_dir_silent(&["restore", "--", path], &repo_root)?;
+        }
     }
+    Ok(())
+}
 
-    println!("✓ Selected files reset.");
+fn reset_hunks() -> Result<()> {
+    backup_before("reset --patch")?;
+    let count = run_hunk_selection(HunkAction::Discard, &[])?;
+    if count == 0 {
+        println!("No hunks discarded.");
+    } else {
+        println!(
+            "✓ Discarded {} hunk(s) (use 'supgit undo' to bring them back)",
+            count
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use dialoguer::{Confirm, Select};
+
+use crate::backup::{find_backup, list_backups, restore_backup};
+
+pub fn run_restore_backup(id: Option<String>, list: bool) -> Result<()> {
+    if list {
+        let backups = list_backups()?;
+        if backups.is_empty() {
+            println!("No backups found.");
+            return Ok(());
+        }
+        for backup in backups.iter().rev() {
+            println!("{}", backup.describe());
+        }
+        return Ok(());
+    }
+
+    let backup = match id {
+        Some(id) => find_backup(&id)?,
+        None => {
+            let mut backups = list_backups()?;
+            if backups.is_empty() {
+                println!("No backups found.");
+                return Ok(());
+            }
+            backups.reverse();
+            let items: Vec<String> = backups.iter().map(|b| b.describe()).collect();
+            let selection = Select::new()
+                .with_prompt("Select a backup to restore")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            backups.swap_remove(selection)
+        }
+    };
+
+    let confirm = Confirm::new()
+        .with_prompt(format!(
+            "Restore backup {} ({})? Files it contains will overwrite your working tree.",
+            backup.id, backup.reason
+        ))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    restore_backup(&backup)?;
+    println!("✓ Restored backup {}", backup.id);
+    Ok(())
+}
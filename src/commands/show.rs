@@ -0,0 +1,191 @@
+use std::io::IsTerminal;
+
+use anyhow::{Result, bail};
+use dialoguer::Select;
+use serde::Serialize;
+
+use super::diff::{
+    DiffScope, FileDiffEntry, build_diff_entries, format_selector_item, show_diff_for_entry,
+};
+use crate::git::git_stdout;
+use crate::json::print_json;
+use crate::render::RenderOptions;
+
+#[derive(Serialize)]
+struct CommitDetails {
+    hash: String,
+    #[serde(skip)]
+    short_hash: String,
+    parents: Vec<String>,
+    author: String,
+    email: String,
+    date: String,
+    #[serde(skip)]
+    age: String,
+    refs: String,
+    message: String,
+    #[serde(skip)]
+    base: String,
+    files: Vec<FileDiffEntry>,
+}
+
+impl CommitDetails {
+    fn scope(&self) -> DiffScope {
+        DiffScope::Range {
+            from: self.base.clone(),
+            to: Some(self.hash.clone()),
+        }
+    }
+}
+
+pub fn run_show(commit: Option<String>, path: Option<String>, json: bool) -> Result<()> {
+    let rev = commit.as_deref().unwrap_or("HEAD");
+    let details = load_commit(rev)?;
+
+    if json {
+        return print_json(&details);
+    }
+
+    print_header(&details);
+    if details.files.is_empty() {
+        println!("No file changes.");
+        return Ok(());
+    }
+
+    if let Some(path) = path {
+        let Some(entry) = details
+            .files
+            .iter()
+            .find(|e| e.git_path == path || e.display_path == path)
+        else {
+            bail!("'{}' was not changed in {}", path, details.short_hash);
+        };
+        return show_diff_for_entry(&details.scope(), entry, RenderOptions::default());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        let scope = details.scope();
+        for entry in &details.files {
+            show_diff_for_entry(&scope, entry, RenderOptions::default())?;
+        }
+        return Ok(());
+    }
+    pick_files(&details)
+}
+
+/// Shows a commit's header and lets the user open its files until "Done".
+pub fn browse_commit(rev: &str) -> Result<()> {
+    let details = load_commit(rev)?;
+    print_header(&details);
+    if details.files.is_empty() {
+        println!("No file changes.");
+        return Ok(());
+    }
+    pick_files(&details)
+}
+
+fn load_commit(rev: &str) -> Result<CommitDetails> {
+    let Some(hash) = git_stdout(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", rev),
+    ]) else {
+        bail!("'{}' is not a commit", rev);
+    };
+
+    let Some(raw) = git_stdout(&[
+        "log",
+        "-1",
+        "--format=%h%x00%P%x00%an%x00%ae%x00%aI%x00%ar%x00%D%x00%B",
+        &hash,
+    ]) else {
+        bail!("could not read commit {}", rev);
+    };
+    let mut fields = raw.splitn(8, '\0');
+    let mut next = || fields.next().unwrap_or_default().to_string();
+    let mut details = CommitDetails {
+        short_hash: next(),
+        parents: next().split_whitespace().map(String::from).collect(),
+        author: next(),
+        email: next(),
+        date: next(),
+        age: next(),
+        refs: next(),
+        message: next().trim_end().to_string(),
+        hash,
+        base: String::new(),
+        files: Vec::new(),
+    };
+    details.base = diff_base(&details.parents)?;
+    details.files = build_diff_entries(&details.scope())?;
+    Ok(details)
+}
+
+/// Merges are shown against their first parent and root commits against
+/// the empty tree, so every commit has a single "before" side.
+fn diff_base(parents: &[String]) -> Result<String> {
+    if let Some(parent) = parents.first() {
+        return Ok(parent.clone());
+    }
+    match git_stdout(&["hash-object", "-t", "tree", "/dev/null"]) {
+        Some(empty_tree) => Ok(empty_tree),
+        None => bail!("could not compute the empty tree"),
+    }
+}
+
+fn print_header(details: &CommitDetails) {
+    let refs = if details.refs.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", details.refs)
+    };
+    println!("commit {}{}", details.hash, refs);
+    if details.parents.len() > 1 {
+        let short: Vec<&str> = details
+            .parents
+            .iter()
+            .map(|p| &p[..p.len().min(7)])
+            .collect();
+        println!(
+            "Merge:  {} (changes shown against the first parent)",
+            short.join(" ")
+        );
+    }
+    println!("Author: {} <{}>", details.author, details.email);
+    println!("Date:   {} ({})", details.age, details.date);
+    println!();
+    for line in details.message.lines() {
+        println!("    {}", line);
+    }
+    println!();
+
+    let additions: usize = details.files.iter().filter_map(|e| e.additions).sum();
+    let deletions: usize = details.files.iter().filter_map(|e| e.deletions).sum();
+    println!(
+        "{} file(s) changed, +{} -{}",
+        details.files.len(),
+        additions,
+        deletions
+    );
+}
+
+fn pick_files(details: &CommitDetails) -> Result<()> {
+    let mut items: Vec<String> = details.files.iter().map(format_selector_item).collect();
+    items.push("Done".to_string());
+
+    let scope = details.scope();
+    let mut cursor = 0;
+    loop {
+        let selection = Select::new()
+            .with_prompt("Select a file to view its changes")
+            .items(&items)
+            .default(cursor)
+            .interact()?;
+        if selection == details.files.len() {
+            return Ok(());
+        }
+        cursor = selection;
+        show_diff_for_entry(&scope, &details.files[selection], RenderOptions::default())?;
+    }
+}
@@ -2,25 +2,34 @@ use anyhow::Result;
 use dialoguer::{MultiSelect, Select};
 
 use crate::git::run_git_silent;
+use crate::hunks::{HunkAction, run_hunk_selection};
+use crate::journal;
 use crate::status::{PorcelainStatus, get_repo_root};
 
-pub fn stage_targets(targets: &[String], all: bool, tracked: bool) -> Result<()> {
-    let is_interactive = targets.is_empty() && !all && !tracked;
+pub fn stage_targets(targets: &[String], all: bool, tracked: bool, patch: bool) -> Result<()> {
+    let is_interactive = targets.is_empty() && !all && !tracked && !patch;
 
     if is_interactive {
         let selection = Select::new()
             .with_prompt("What would you like to stage?")
-            .items(&["All files", "Tracked files only", "Specific files"])
+            .items(&[
+                "All files",
+                "Tracked files only",
+                "Specific files",
+                "Stage hunks...",
+            ])
             .default(0)
             .interact()?;
 
         match selection {
             0 => {
+                journal::record("stage --all")?;
                 run_git_silent(&["add", "-A"])?;
                 println!("✓ Staged all files");
                 Ok(())
             }
             1 => {
+                journal::record("stage --tracked")?;
                 run_git_silent(&["add", "-u"])?;
                 println!("✓ Staged tracked files");
                 Ok(())
@@ -43,24 +52,26 @@ pub fn stage_targets(targets: &[String], all: bool, tracked: bool) -> Result<()>
                     return Ok(());
                 }
 
-                let repo_root = get_repo_root()?;
-                let mut args = vec!["add".to_string()];
-                let count = selected.len();
-                for idx in selected {
-                    args.push(files_owned[idx].clone());
-                }
-                let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-                crate::git::run_git_in_dir_silent(&args_refs, &repo_root)?;
-                println!("✓ Staged {} file(s)", count);
+                let paths: Vec<String> = selected
+                    .iter()
+                    .map(|&idx| files_owned[idx].clone())
+                    .collect();
+                stage_paths(&paths)?;
+                println!("✓ Staged {} file(s)", paths.len());
                 Ok(())
             }
+            3 => stage_hunks(&[]),
             _ => Ok(()),
         }
+    } else if patch {
+        stage_hunks(targets)
     } else if all {
+        journal::record("stage --all")?;
         run_git_silent(&["add", "-A"])?;
         println!("✓ Staged all files");
         Ok(())
     } else if tracked {
+        journal::record("stage --tracked")?;
         run_git_silent(&["add", "-u"])?;
         println!("✓ Staged tracked files");
         Ok(())
@@ -75,8 +86,28 @@ pub fn stage_targets(targets: &[String], all: bool, tracked: bool) -> Result<()>
         args.push("add");
         args.extend(target_args);
 
+        journal::record("stage")?;
         run_git_silent(&args)?;
         println!("✓ Staged files");
         Ok(())
     }
 }
+
+/// Stages whole files, given as paths relative to the repository root.
+pub fn stage_paths(paths: &[String]) -> Result<()> {
+    journal::record("stage")?;
+    let repo_root = get_repo_root()?;
+    let mut args = vec!["add", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    crate::git::run_git_in_dir_silent(&args, &repo_root)
+}
+
+fn stage_hunks(paths: &[String]) -> Result<()> {
+    let count = run_hunk_selection(HunkAction::Stage, paths)?;
+    if count == 0 {
+        println!("No hunks staged.");
+    } else {
+        println!("✓ Staged {} hunk(s)", count);
+    }
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use std::process::Command as StdCommand;
+
+use anyhow::{Context, Result, bail};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+
+use crate::git::{git_stdout, run_git_quiet};
+use crate::journal;
+use crate::status::{StatusEntry, get_status_entries};
+
+pub struct StashOptions {
+    pub save: bool,
+    pub message: Option<String>,
+    pub untracked: bool,
+    pub paths: Vec<String>,
+    pub list: bool,
+    pub show: bool,
+    pub apply: bool,
+    pub pop: bool,
+    pub drop: bool,
+    pub clear: bool,
+}
+
+pub struct StashEntry {
+    pub name: String,
+    pub age: String,
+    pub subject: String,
+}
+
+impl StashEntry {
+    fn summary(&self) -> String {
+        let files = stash_files(&self.name);
+        let file_text = match files.len() {
+            0 => "no files".to_string(),
+            1..=3 => format!("{} file(s): {}", files.len(), files.join(", ")),
+            n => format!("{} files: {}, …", n, files[..3].join(", ")),
+        };
+        format!(
+            "{}  {} ({}) — {}",
+            self.name, self.subject, self.age, file_text
+        )
+    }
+}
+
+pub fn run_stash(options: StashOptions) -> Result<()> {
+    let is_save =
+        options.save || options.message.is_some() || options.untracked || !options.paths.is_empty();
+
+    if is_save {
+        save_stash(options.message, options.untracked, &options.paths)
+    } else if options.list {
+        list_stashes()
+    } else if options.show {
+        show_stash()
+    } else if options.apply {
+        apply_stash(false)
+    } else if options.pop {
+        apply_stash(true)
+    } else if options.drop {
+        drop_stash()
+    } else if options.clear {
+        clear_stashes()
+    } else {
+        run_stash_interactive()
+    }
+}
+
+fn run_stash_interactive() -> Result<()> {
+    let selection = Select::new()
+        .with_prompt("What would you like to do?")
+        .items(&[
+            "Save changes",
+            "Save changes including untracked files",
+            "Save selected files",
+            "List stashes",
+            "Show a stash",
+            "Apply a stash",
+            "Pop a stash (apply and remove)",
+            "Drop a stash",
+            "Clear all stashes",
+        ])
+        .default(0)
+        .interact()?;
+
+    match selection {
+        0 | 1 => {
+            let message = prompt_message()?;
+            save_stash(message, selection == 1, &[])
+        }
+        2 => save_selected_files(),
+        3 => list_stashes(),
+        4 => show_stash(),
+        5 => apply_stash(false),
+        6 => apply_stash(true),
+        7 => drop_stash(),
+        8 => clear_stashes(),
+        _ => Ok(()),
+    }
+}
+
+fn prompt_message() -> Result<Option<String>> {
+    let message: String = Input::new()
+        .with_prompt("Stash message (optional)")
+        .allow_empty(true)
+        .interact_text()?;
+    let message = message.trim().to_string();
+    Ok((!message.is_empty()).then_some(message))
+}
+
+fn save_stash(message: Option<String>, untracked: bool, paths: &[String]) -> Result<()> {
+    let entries = get_status_entries()?;
+    let has_changes = entries.iter().any(|e| untracked || !e.is_untracked());
+    if !has_changes && paths.is_empty() {
+        println!("No local changes to stash.");
+        return Ok(());
+    }
+
+    let mut args = vec!["stash", "push"];
+    if untracked {
+        args.push("--include-untracked");
+    }
+    if let Some(ref message) = message {
+        args.push("-m");
+        args.push(message);
+    }
+    if !paths.is_empty() {
+        args.push("--");
+        args.extend(paths.iter().map(String::as_str));
+    }
+
+    journal::record("stash save")?;
+    run_git_quiet(&args)?;
+    println!("✓ Saved changes to stash@{{0}}");
+    Ok(())
+}
+
+fn save_selected_files() -> Result<()> {
+    let entries = get_status_entries()?;
+    if entries.is_empty() {
+        println!("No local changes to stash.");
+        return Ok(());
+    }
+
+    let items: Vec<String> = entries.iter().map(StatusEntry::display_path).collect();
+    let selected = MultiSelect::new()
+        .with_prompt("Select files to stash")
+        .items(&items)
+        .interact()?;
+    if selected.is_empty() {
+        println!("No files selected.");
+        return Ok(());
+    }
+
+    let untracked = selected.iter().any(|&idx| entries[idx].is_untracked());
+    let mut paths = Vec::new();
+    for idx in selected {
+        paths.push(entries[idx].path.clone());
+        paths.extend(entries[idx].orig_path.clone());
+    }
+    let message = prompt_message()?;
+    save_stash(message, untracked, &paths)
+}
+
+pub fn get_stash_entries() -> Result<Vec<StashEntry>> {
+    let output = StdCommand::new("git")
+        .args(["stash", "list", "--format=%gd%x00%cr%x00%gs"])
+        .output()
+        .context("running git stash list")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git stash list failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let entries = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\0');
+            Some(StashEntry {
+                name: parts.next()?.to_string(),
+                age: parts.next()?.to_string(),
+                subject: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn stash_files(name: &str) -> Vec<String> {
+    git_stdout(&["stash", "show", "--include-untracked", "--name-only", name])
+        .map(|s| s.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+fn pick_stash(prompt: &str) -> Result<Option<StashEntry>> {
+    let mut entries = get_stash_entries()?;
+    if entries.is_empty() {
+        println!("No stashes found.");
+        return Ok(None);
+    }
+    let items: Vec<String> = entries.iter().map(StashEntry::summary).collect();
+    let selection = Select::new()
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(Some(entries.swap_remove(selection)))
+}
+
+fn list_stashes() -> Result<()> {
+    let entries = get_stash_entries()?;
+    if entries.is_empty() {
+        println!("No stashes found.");
+        return Ok(());
+    }
+    for entry in entries {
+        println!("{}", entry.summary());
+    }
+    Ok(())
+}
+
+fn show_stash() -> Result<()> {
+    let Some(entry) = pick_stash("Select a stash to show")? else {
+        return Ok(());
+    };
+
+    let output = StdCommand::new("git")
+        .args([
+            "-c",
+            "color.ui=always",
+            "stash",
+            "show",
+            "-p",
+            "--include-untracked",
+            &entry.name,
+        ])
+        .output()
+        .context("running git stash show")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git stash show failed:\n  {}", stderr.trim());
+    }
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    Ok(())
+}
+
+fn apply_stash(pop: bool) -> Result<()> {
+    let prompt = if pop {
+        "Select a stash to pop"
+    } else {
+        "Select a stash to apply"
+    };
+    let Some(entry) = pick_stash(prompt)? else {
+        return Ok(());
+    };
+
+    let verb = if pop { "pop" } else { "apply" };
+    journal::record(&format!("stash {}", verb))?;
+    if let Err(e) = run_git_quiet(&["stash", verb, &entry.name]) {
+        let err_str = e.to_string().to_lowercase();
+        if err_str.contains("conflict") {
+            eprintln!("✗ Applying {} caused conflicts", entry.name);
+            eprintln!("  Resolve them, then run 'supgit stage <path>' for each file.");
+            if pop {
+                eprintln!("  The stash was kept; drop it with 'supgit stash --drop' once done.");
+            }
+        }
+        return Err(e);
+    }
+
+    if pop {
+        println!("✓ Applied and removed {}", entry.name);
+    } else {
+        println!("✓ Applied {}", entry.name);
+    }
+    Ok(())
+}
+
+fn drop_stash() -> Result<()> {
+    let Some(entry) = pick_stash("Select a stash to drop")? else {
+        return Ok(());
+    };
+
+    let confirm = Confirm::new()
+        .with_prompt(format!("Drop {} ({})?", entry.name, entry.subject))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let sha = git_stdout(&["rev-parse", "--short", &entry.name]).unwrap_or_default();
+    run_git_quiet(&["stash", "drop", &entry.name])?;
+    println!("✓ Dropped {} ({})", entry.name, sha);
+    Ok(())
+}
+
+fn clear_stashes() -> Result<()> {
+    let entries = get_stash_entries()?;
+    if entries.is_empty() {
+        println!("No stashes found.");
+        return Ok(());
+    }
+
+    let confirm = Confirm::new()
+        .with_prompt(format!(
+            "Delete all {} stash(es)? This cannot be undone.",
+            entries.len()
+        ))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    run_git_quiet(&["stash", "clear"])?;
+    println!("✓ Cleared {} stash(es)", entries.len());
+    Ok(())
+}
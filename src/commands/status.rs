@@ -0,0 +1,207 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::json::print_json;
+use crate::status::{
+    InProgress, PorcelainStatus, StatusEntry, get_in_progress_operation, get_stash_count,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Conflicted,
+    Staged,
+    Unstaged,
+    Untracked,
+}
+
+#[derive(Serialize)]
+struct StatusJson<'a> {
+    branch: Option<String>,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    stashes: usize,
+    in_progress: Option<InProgress>,
+    files: &'a [StatusEntry],
+}
+
+pub fn run_status(short: bool, json: bool) -> Result<()> {
+    let status = PorcelainStatus::parse()?;
+    let in_progress = get_in_progress_operation()?;
+    let stashes = get_stash_count();
+
+    if json {
+        return print_status_json(&status, in_progress, stashes);
+    }
+
+    if short {
+        print_short_status(&status);
+    } else {
+        print_full_status(&status, in_progress, stashes);
+    }
+    Ok(())
+}
+
+fn sections_for(entry: &StatusEntry) -> Vec<Section> {
+    if entry.is_untracked() {
+        return vec![Section::Untracked];
+    }
+    if entry.is_conflicted() {
+        return vec![Section::Conflicted];
+    }
+    let mut sections = Vec::new();
+    if entry.is_staged() {
+        sections.push(Section::Staged);
+    }
+    if entry.is_unstaged() {
+        sections.push(Section::Unstaged);
+    }
+    sections
+}
+
+fn branch_line(status: &PorcelainStatus) -> String {
+    let branch = status.branch();
+    let mut line = match (&branch.head, &branch.oid) {
+        (Some(head), Some(_)) => format!("On branch {}", head),
+        (Some(head), None) => format!("On branch {} (no commits yet)", head),
+        (None, Some(oid)) => format!("HEAD detached at {}", &oid[..oid.len().min(7)]),
+        (None, None) => "HEAD detached".to_string(),
+    };
+    if let Some(ref upstream) = branch.upstream {
+        line.push_str(&format!(" → {}", upstream));
+        match (branch.ahead, branch.behind) {
+            (0, 0) => line.push_str(" (up to date)"),
+            (ahead, 0) => line.push_str(&format!(" (↑{} ahead)", ahead)),
+            (0, behind) => line.push_str(&format!(" (↓{} behind)", behind)),
+            (ahead, behind) => line.push_str(&format!(" (↑{} ↓{} diverged)", ahead, behind)),
+        }
+    } else if branch.head.is_some() && branch.oid.is_some() {
+        line.push_str(" (no upstream)");
+    }
+    line
+}
+
+fn print_short_status(status: &PorcelainStatus) {
+    println!("{}", branch_line(status));
+    for entry in status.entries() {
+        let code = if entry.is_untracked() {
+            "??".to_string()
+        } else {
+            format!(
+                "{}{}",
+                entry.index.short_code(),
+                entry.worktree.short_code()
+            )
+        };
+        println!("  {} {}", code, entry.display_path());
+    }
+}
+
+fn print_full_status(status: &PorcelainStatus, in_progress: Option<InProgress>, stashes: usize) {
+    println!("{}", branch_line(status));
+    if let Some(op) = in_progress {
+        println!("⚠ {} in progress", op.label());
+    }
+    if stashes > 0 {
+        println!("Stashes: {}", stashes);
+    }
+
+    let sections = [
+        (Section::Conflicted, "Conflicted"),
+        (Section::Staged, "Staged"),
+        (Section::Unstaged, "Unstaged"),
+        (Section::Untracked, "Untracked"),
+    ];
+    for (section, title) in sections {
+        let lines: Vec<String> = status
+            .entries()
+            .iter()
+            .filter(|entry| sections_for(entry).contains(&section))
+            .map(|entry| match section {
+                Section::Conflicted => {
+                    let kind = entry.conflict.map(|c| c.label()).unwrap_or("conflicted");
+                    format!("{}: {}", kind, entry.path)
+                }
+                Section::Staged => format!("{}: {}", entry.index.label(), entry.display_path()),
+                Section::Unstaged => format!("{}: {}", entry.worktree.label(), entry.path),
+                Section::Untracked => entry.path.clone(),
+            })
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{} ({}):", title, lines.len());
+        for line in lines {
+            println!("  {}", line);
+        }
+    }
+
+    println!();
+    println!("→ {}", suggest_next_step(status, in_progress));
+}
+
+fn suggest_next_step(status: &PorcelainStatus, in_progress: Option<InProgress>) -> String {
+    let has = |section: Section| {
+        status
+            .entries()
+            .iter()
+            .any(|entry| sections_for(entry).contains(&section))
+    };
+
+    if has(Section::Conflicted) {
+        return "resolve the conflicted files, then run 'supgit stage <path>' for each".to_string();
+    }
+    match in_progress {
+        Some(InProgress::Merge) => {
+            return "all conflicts resolved - run 'supgit commit' to finish the merge".to_string();
+        }
+        Some(InProgress::Rebase) => {
+            return "run 'git rebase --continue' to finish the rebase".to_string();
+        }
+        Some(InProgress::CherryPick) => {
+            return "run 'git cherry-pick --continue' to finish the cherry-pick".to_string();
+        }
+        Some(InProgress::Revert) => {
+            return "run 'git revert --continue' to finish the revert".to_string();
+        }
+        Some(InProgress::Bisect) | None => {}
+    }
+    if has(Section::Staged) {
+        return "run 'supgit commit' to commit the staged changes".to_string();
+    }
+    if has(Section::Unstaged) || has(Section::Untracked) {
+        return "run 'supgit stage' to pick changes for the next commit".to_string();
+    }
+
+    let branch = status.branch();
+    if branch.oid.is_none() {
+        return "create some files, then run 'supgit stage'".to_string();
+    }
+    match (branch.upstream.is_some(), branch.ahead, branch.behind) {
+        (false, _, _) => "working tree clean - nothing to commit (no upstream set)".to_string(),
+        (true, a, b) if a > 0 && b > 0 => {
+            "run 'supgit sync' to reconcile with the remote".to_string()
+        }
+        (true, a, _) if a > 0 => "run 'supgit push' to publish your commits".to_string(),
+        (true, _, b) if b > 0 => "run 'supgit pull' to get the latest commits".to_string(),
+        _ => "working tree clean - nothing to do".to_string(),
+    }
+}
+
+fn print_status_json(
+    status: &PorcelainStatus,
+    in_progress: Option<InProgress>,
+    stashes: usize,
+) -> Result<()> {
+    let branch = status.branch();
+    print_json(&StatusJson {
+        branch: branch.head.clone(),
+        upstream: branch.upstream.clone(),
+        ahead: branch.ahead,
+        behind: branch.behind,
+        stashes,
+        in_progress,
+        files: status.entries(),
+    })
+}
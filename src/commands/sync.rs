@@ -1,13 +1,17 @@
 use anyhow::Result;
 
 use crate::git::run_git_quiet;
+use crate::journal;
+use crate::scan;
 use crate::status::get_current_branch;
 
-pub fn run_push(remote: Option<String>, branch: Option<String>) -> Result<()> {
+pub fn run_push(remote: Option<String>, branch: Option<String>, allow_secrets: bool) -> Result<()> {
     if remote.is_none() && branch.is_some() {
         anyhow::bail!("cannot specify --branch without --remote");
     }
 
+    scan::guard_push(branch.as_deref(), allow_secrets)?;
+
     print!("→ Pushing");
     if let Some(ref r) = remote {
         print!(" to {}", r);
@@ -55,11 +59,13 @@ pub fn run_pull(remote: Option<String>, branch: Option<String>) -> Result<()> {
     Ok(())
 }
 
-pub fn run_sync(remote: Option<&str>, branch: Option<&str>) -> Result<()> {
+pub fn run_sync(remote: Option<&str>, branch: Option<&str>, allow_secrets: bool) -> Result<()> {
     let remote_name = remote.unwrap_or("origin");
     let current_branch = get_current_branch().unwrap_or_default();
     let is_main = current_branch == "main" || current_branch == "master";
 
+    journal::record("sync")?;
+
     println!("→ Fetching from {}...", remote_name);
     let fetch_result = run_git_quiet(&["fetch", remote_name]);
     if let Err(e) = fetch_result {
@@ -139,6 +145,7 @@ pub fn run_sync(remote: Option<&str>, branch: Option<&str>) -> Result<()> {
     }
 
     println!("→ Pushing changes...");
+    scan::guard_push(branch, allow_secrets)?;
     let mut push_args = vec!["push"];
     let mut push_owned: Vec<String> = Vec::new();
     if let Some(r) = remote {
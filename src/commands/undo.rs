@@ -0,0 +1,40 @@
+use anyhow::Result;
+use dialoguer::Confirm;
+
+use crate::journal::{load_entries, pop_entry, restore};
+
+pub fn run_undo(list: bool) -> Result<()> {
+    let entries = load_entries()?;
+
+    if list {
+        if entries.is_empty() {
+            println!("No SupGIT operations recorded yet.");
+            return Ok(());
+        }
+        for entry in entries.iter().rev() {
+            println!("{}", entry.describe());
+        }
+        return Ok(());
+    }
+
+    let Some(entry) = entries.last() else {
+        println!("Nothing to undo.");
+        return Ok(());
+    };
+
+    println!("Last operation: {}", entry.describe());
+    println!("  Uncommitted changes made since then will be replaced.");
+    let confirm = Confirm::new()
+        .with_prompt(format!("Undo '{}'?", entry.operation))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    restore(entry)?;
+    pop_entry()?;
+    println!("✓ Undid '{}'", entry.operation);
+    Ok(())
+}
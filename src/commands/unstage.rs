@@ -2,33 +2,40 @@ use anyhow::Result;
 use dialoguer::{MultiSelect, Select};
 
 use crate::git::run_git_silent;
-use crate::status::{get_repo_root, get_staged_files};
+use crate::hunks::{HunkAction, run_hunk_selection};
+use crate::journal;
+use crate::status::{StatusEntry, get_repo_root, get_status_entries};
 
-pub fn restore_stage(targets: &[String], all: bool) -> Result<()> {
-    let is_interactive = targets.is_empty() && !all;
+pub fn restore_stage(targets: &[String], all: bool, patch: bool) -> Result<()> {
+    let is_interactive = targets.is_empty() && !all && !patch;
 
     if is_interactive {
         let selection = Select::new()
             .with_prompt("What would you like to unstage?")
-            .items(&["All staged files", "Specific files"])
+            .items(&["All staged files", "Specific files", "Unstage hunks..."])
             .default(0)
             .interact()?;
 
         match selection {
             0 => {
+                journal::record("unstage --all")?;
                 run_git_silent(&["restore", "--staged", "."])?;
                 println!("✓ All files unstaged");
                 Ok(())
             }
             1 => {
-                let files = get_staged_files()?;
-                if files.is_empty() {
+                let entries: Vec<StatusEntry> = get_status_entries()?
+                    .into_iter()
+                    .filter(StatusEntry::is_staged)
+                    .collect();
+                if entries.is_empty() {
                     println!("No staged files to unstage.");
                     return Ok(());
                 }
+                let items: Vec<String> = entries.iter().map(StatusEntry::display_path).collect();
                 let selected = MultiSelect::new()
                     .with_prompt("Select files to unstage")
-                    .items(&files)
+                    .items(&items)
                     .interact()?;
 
                 if selected.is_empty() {
@@ -36,20 +43,25 @@ pub fn restore_stage(targets: &[String], all: bool) -> Result<()> {
                     return Ok(());
                 }
 
-                let repo_root = get_repo_root()?;
-                let mut args = vec!["restore".to_string(), "--staged".to_string()];
-                let count = selected.len();
-                for idx in selected {
-                    args.push(files[idx].clone());
-                }
-                let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-                crate::git::run_git_in_dir_silent(&args_refs, &repo_root)?;
-                println!("✓ Unstaged {} file(s)", count);
+                // A rename is only undone when its old path is restored too.
+                let paths: Vec<String> = selected
+                    .iter()
+                    .flat_map(|&idx| {
+                        std::iter::once(entries[idx].path.clone())
+                            .chain(entries[idx].orig_path.clone())
+                    })
+                    .collect();
+                unstage_paths(&paths)?;
+                println!("✓ Unstaged {} file(s)", selected.len());
                 Ok(())
             }
+            2 => unstage_hunks(&[]),
             _ => Ok(()),
         }
+    } else if patch {
+        unstage_hunks(targets)
     } else if all {
+        journal::record("unstage --all")?;
         run_git_silent(&["restore", "--staged", "."])?;
         println!("✓ All files unstaged");
         Ok(())
@@ -65,8 +77,28 @@ pub fn restore_stage(targets: &[String], all: bool) -> Result<()> {
         args.push("--staged");
         args.extend(target_args);
 
+        journal::record("unstage")?;
         run_git_silent(&args)?;
         println!("✓ Files unstaged");
         Ok(())
     }
 }
+
+/// Unstages whole files, given as paths relative to the repository root.
+pub fn unstage_paths(paths: &[String]) -> Result<()> {
+    journal::record("unstage")?;
+    let repo_root = get_repo_root()?;
+    let mut args = vec!["restore", "--staged", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    crate::git::run_git_in_dir_silent(&args, &repo_root)
+}
+
+fn unstage_hunks(paths: &[String]) -> Result<()> {
+    let count = run_hunk_selection(HunkAction::Unstage, paths)?;
+    if count == 0 {
+        println!("No hunks unstaged.");
+    } else {
+        println!("✓ Unstaged {} hunk(s)", count);
+    }
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use anyhow::{Result, bail};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::git::git_stdout;
+
+/// Longest header (`type(scope)!: subject`) most changelog tools accept.
+pub const MAX_HEADER_LENGTH: usize = 72;
+
+pub const COMMIT_TYPES: &[(&str, &str)] = &[
+    ("feat", "a new feature"),
+    ("fix", "a bug fix"),
+    ("docs", "documentation only changes"),
+    ("style", "formatting, missing semicolons, etc."),
+    (
+        "refactor",
+        "code change that neither fixes a bug nor adds a feature",
+    ),
+    ("perf", "a performance improvement"),
+    ("test", "adding or correcting tests"),
+    ("build", "build system or dependency changes"),
+    ("ci", "CI configuration changes"),
+    ("chore", "other changes that don't touch src or tests"),
+    ("revert", "reverts a previous commit"),
+];
+
+/// Whether the repository opted into the composer with
+/// `git config supgit.conventional true`.
+pub fn enabled_by_config() -> bool {
+    git_stdout(&["config", "--bool", "supgit.conventional"]).as_deref() == Some("true")
+}
+
+pub struct ConventionalMessage {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub subject: String,
+    pub body: Option<String>,
+    pub footers: Vec<String>,
+}
+
+impl ConventionalMessage {
+    pub fn header(&self) -> String {
+        let scope = self
+            .scope
+            .as_ref()
+            .map(|s| format!("({})", s))
+            .unwrap_or_default();
+        let bang = if self.breaking { "!" } else { "" };
+        format!("{}{}{}: {}", self.commit_type, scope, bang, self.subject)
+    }
+
+    pub fn render(&self) -> String {
+        let mut message = self.header();
+        if let Some(ref body) = self.body {
+            message.push_str("\n\n");
+            message.push_str(body);
+        }
+        if !self.footers.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&self.footers.join("\n"));
+        }
+        message
+    }
+}
+
+/// Parses and validates the first line of `message` as a Conventional
+/// Commits header.
+pub fn validate_header(message: &str) -> Result<()> {
+    let header = message.lines().next().unwrap_or_default();
+    let Some((prefix, subject)) = header.split_once(": ") else {
+        bail!(
+            "commit header '{}' must look like 'type(scope): subject'",
+            header
+        );
+    };
+
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let commit_type = match prefix.split_once('(') {
+        Some((commit_type, scope)) => {
+            let Some(scope) = scope.strip_suffix(')') else {
+                bail!("commit scope in '{}' is missing a closing ')'", header);
+            };
+            validate_scope(scope)?;
+            commit_type
+        }
+        None => prefix,
+    };
+
+    if !COMMIT_TYPES.iter().any(|(name, _)| *name == commit_type) {
+        let names: Vec<&str> = COMMIT_TYPES.iter().map(|(name, _)| *name).collect();
+        bail!(
+            "unknown commit type '{}'; use one of: {}",
+            commit_type,
+            names.join(", ")
+        );
+    }
+    validate_subject(subject)?;
+    if header.chars().count() > MAX_HEADER_LENGTH {
+        bail!(
+            "commit header is {} characters; keep it at {} or fewer",
+            header.chars().count(),
+            MAX_HEADER_LENGTH
+        );
+    }
+    Ok(())
+}
+
+fn validate_scope(scope: &str) -> Result<()> {
+    if scope.is_empty() {
+        bail!("commit scope cannot be empty; drop the parentheses instead");
+    }
+    if scope.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        bail!(
+            "commit scope '{}' cannot contain spaces or parentheses",
+            scope
+        );
+    }
+    Ok(())
+}
+
+fn validate_subject(subject: &str) -> Result<()> {
+    let subject = subject.trim();
+    if subject.is_empty() {
+        bail!("commit subject cannot be empty");
+    }
+    if subject.ends_with('.') {
+        bail!("commit subject should not end with a period");
+    }
+    Ok(())
+}
+
+fn validate_footer(footer: &str) -> Result<()> {
+    let is_token_footer = footer
+        .split_once(": ")
+        .or_else(|| footer.split_once(" #"))
+        .is_some_and(|(token, value)| {
+            !value.trim().is_empty()
+                && (token == "BREAKING CHANGE"
+                    || token.chars().all(|c| c.is_alphanumeric() || c == '-'))
+        });
+    if !is_token_footer {
+        bail!(
+            "footer '{}' must look like 'Token: value' or 'Token #value'",
+            footer
+        );
+    }
+    Ok(())
+}
+
+/// Walks the user through type, scope, breaking flag, subject, body and
+/// footers, then returns the assembled message.
+pub fn compose() -> Result<String> {
+    let items: Vec<String> = COMMIT_TYPES
+        .iter()
+        .map(|(name, description)| format!("{:<9} {}", name, description))
+        .collect();
+    let selection = Select::new()
+        .with_prompt("Type of change")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    let commit_type = COMMIT_TYPES[selection].0.to_string();
+
+    let scope: String = Input::new()
+        .with_prompt("Scope (optional, e.g. parser)")
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), String> {
+            if input.trim().is_empty() {
+                return Ok(());
+            }
+            validate_scope(input.trim()).map_err(|e| e.to_string())
+        })
+        .interact_text()?;
+    let scope = scope.trim().to_string();
+    let scope = (!scope.is_empty()).then_some(scope);
+
+    let breaking = Confirm::new()
+        .with_prompt("Is this a breaking change?")
+        .default(false)
+        .interact()?;
+
+    let prefix_len = ConventionalMessage {
+        commit_type: commit_type.clone(),
+        scope: scope.clone(),
+        breaking,
+        subject: String::new(),
+        body: None,
+        footers: Vec::new(),
+    }
+    .header()
+    .chars()
+    .count();
+    let max_subject = MAX_HEADER_LENGTH.saturating_sub(prefix_len);
+
+    let subject: String = Input::new()
+        .with_prompt(format!("Short description (max {} chars)", max_subject))
+        .validate_with(|input: &String| -> Result<(), String> {
+            validate_subject(input).map_err(|e| e.to_string())?;
+            let len = input.trim().chars().count();
+            if len > max_subject {
+                return Err(format!(
+                    "description is {} characters; keep it at {} or fewer",
+                    len, max_subject
+                ));
+            }
+            Ok(())
+        })
+        .interact_text()?;
+
+    let body: String = Input::new()
+        .with_prompt("Longer description (optional)")
+        .allow_empty(true)
+        .interact_text()?;
+    let body = body.trim().to_string();
+
+    let mut footers = Vec::new();
+    if breaking {
+        let description: String = Input::new()
+            .with_prompt("Describe the breaking change")
+            .interact_text()?;
+        footers.push(format!("BREAKING CHANGE: {}", description.trim()));
+    }
+    loop {
+        let footer: String = Input::new()
+            .with_prompt("Footer (e.g. Closes #123; leave empty to finish)")
+            .allow_empty(true)
+            .validate_with(|input: &String| -> Result<(), String> {
+                if input.trim().is_empty() {
+                    return Ok(());
+                }
+                validate_footer(input.trim()).map_err(|e| e.to_string())
+            })
+            .interact_text()?;
+        if footer.trim().is_empty() {
+            break;
+        }
+        footers.push(footer.trim().to_string());
+    }
+
+    let message = ConventionalMessage {
+        commit_type,
+        scope,
+        breaking,
+        subject: subject.trim().to_string(),
+        body: (!body.is_empty()).then_some(body),
+        footers,
+    }
+    .render();
+
+    println!();
+    println!("→ Commit message:");
+    for line in message.lines() {
+        if line.is_empty() {
+            println!();
+        } else {
+            println!("  {}", line);
+        }
+    }
+    println!();
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(message: &str) -> String {
+        validate_header(message).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn accepts_valid_headers() {
+        for header in [
+            "feat: add login",
+            "fix(parser): handle tabs",
+            "feat(api)!: drop v1 endpoints",
+            "refactor!: split modules",
+            "docs: explain setup\n\nThe body is not checked.",
+        ] {
+            assert!(validate_header(header).is_ok(), "{}", header);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_prefixes() {
+        assert!(error("add login").contains("must look like"));
+        assert!(error("feat:add login").contains("must look like"));
+        assert!(error("feature: add login").contains("unknown commit type 'feature'"));
+        assert!(error("Feat: add login").contains("unknown commit type"));
+        assert!(error("fix(parser: handle tabs").contains("closing ')'"));
+        assert!(error("fix(): handle tabs").contains("cannot be empty"));
+        assert!(error("fix(my parser): handle tabs").contains("spaces or parentheses"));
+    }
+
+    #[test]
+    fn rejects_bad_subjects() {
+        assert!(error("fix:  ").contains("subject cannot be empty"));
+        assert!(error("fix: handle tabs.").contains("period"));
+    }
+
+    #[test]
+    fn limits_header_length() {
+        let subject = "x".repeat(MAX_HEADER_LENGTH - "fix: ".len());
+        assert!(validate_header(&format!("fix: {}", subject)).is_ok());
+        assert!(error(&format!("fix: {}y", subject)).contains("keep it at 72"));
+    }
+}
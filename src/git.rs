@@ -24,6 +24,8 @@ fn invalidate_cache_if_modifying(args: &[&str]) {
             "cherry-pick",
             "revert",
             "stash",
+            "clean",
+            "read-tree",
         ];
         if modifying_commands.contains(cmd) {
             invalidate_porcelain_cache();
@@ -31,36 +33,6 @@ fn invalidate_cache_if_modifying(args: &[&str]) {
     }
 }
 
-pub fn run_git(args: &[&str]) -> Result<()> {
-    let output = StdCommand::new("git")
-        .args(args)
-        .output()
-        .with_context(|| {
-            format!(
-                "failed to execute git {} - is git installed?",
-                args.join(" ")
-            )
-        })?;
-
-    if output.status.success() {
-        invalidate_cache_if_modifying(args);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if !stdout.is_empty() {
-            print!("{}", stdout);
-        }
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let hint = suggest_hint_for_git_error(&stderr, args);
-        bail!(
-            "git {} failed:{}{}",
-            args.join(" "),
-            format_stderr(&stderr),
-            hint
-        );
-    }
-}
-
 pub fn run_git_quiet(args: &[&str]) -> Result<()> {
     let output = StdCommand::new("git")
         .args(args)
@@ -105,13 +77,7 @@ pub fn run_git_silent(args: &[&str]) -> Result<()> {
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let hint = suggest_hint_for_git_error(&stderr, args);
-        bail!(
-            "git {} failed:{}{}",
-            args.join(" "),
-            format_stderr(&stderr),
-            hint
-        );
+        Err(git_failure(args, &stderr))
     }
 }
 
@@ -145,6 +111,17 @@ pub fn run_git_in_dir_silent(args: &[&str], dir: &str) -> Result<()> {
     }
 }
 
+/// Runs a read-only git query and returns its trimmed stdout, or `None` when
+/// the command fails or prints nothing.
+pub fn git_stdout(args: &[&str]) -> Option<String> {
+    let output = StdCommand::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
 pub fn check_in_repo() -> Result<()> {
     StdCommand::new("git")
         .args(["rev-parse", "--git-dir"])
@@ -157,6 +134,17 @@ pub fn check_in_repo() -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!("{}", NOT_IN_REPO_HINT))
 }
 
+/// The error `run_git_silent` would report for a git command that already
+/// ran and failed with `stderr`.
+pub fn git_failure(args: &[&str], stderr: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "git {} failed:{}{}",
+        args.join(" "),
+        format_stderr(stderr),
+        suggest_hint_for_git_error(stderr, args)
+    )
+}
+
 fn format_stderr(stderr: &str) -> String {
     let trimmed = stderr.trim();
     if trimmed.is_empty() {
@@ -241,13 +229,13 @@ fn suggest_hint_for_git_error(stderr: &str, args: &[&str]) -> String {
 
     if cmd == "checkout" || cmd == "switch" {
         if stderr_lower.contains("would be overwritten") {
-            return "\n  hint: commit or stash your changes before switching branches".to_string();
+            return "\n  hint: commit or stash ('supgit stash') your changes before switching branches".to_string();
         }
         if stderr_lower.contains("did not match") {
             return "\n  hint: branch name may be misspelled - check 'supgit branch' for available branches".to_string();
         }
         if stderr_lower.contains("your local changes would be overwritten") {
-            return "\n  hint: commit or stash your changes before switching branches".to_string();
+            return "\n  hint: commit or stash ('supgit stash') your changes before switching branches".to_string();
         }
     }
 
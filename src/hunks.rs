@@ -0,0 +1,454 @@
+use std::io::Write;
+use std::process::{Command as StdCommand, Stdio};
+
+use anyhow::{Context, Result, bail};
+use dialoguer::Select;
+
+use crate::journal;
+use crate::status::{get_repo_root, invalidate_porcelain_cache};
+
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HunkAction {
+    Stage,
+    Unstage,
+    Discard,
+}
+
+impl HunkAction {
+    fn verb(self) -> &'static str {
+        match self {
+            HunkAction::Stage => "Stage",
+            HunkAction::Unstage => "Unstage",
+            HunkAction::Discard => "Discard",
+        }
+    }
+
+    fn journal_label(self) -> &'static str {
+        match self {
+            HunkAction::Stage => "stage --patch",
+            HunkAction::Unstage => "unstage --patch",
+            HunkAction::Discard => "reset --patch",
+        }
+    }
+
+    fn diff_args(self) -> Vec<&'static str> {
+        let mut args = vec![
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ];
+        if self == HunkAction::Unstage {
+            args.push("--cached");
+        }
+        args
+    }
+
+    fn apply_args(self) -> Vec<&'static str> {
+        match self {
+            HunkAction::Stage => vec!["apply", "--cached"],
+            HunkAction::Unstage => vec!["apply", "--cached", "-R"],
+            HunkAction::Discard => vec!["apply", "-R"],
+        }
+    }
+
+    /// Whether the patch is applied with `-R`, against the diff's postimage.
+    fn reverse(self) -> bool {
+        self != HunkAction::Stage
+    }
+}
+
+struct Hunk {
+    old_start: usize,
+    new_start: usize,
+    section: String,
+    lines: Vec<String>,
+}
+
+impl Hunk {
+    fn parse_header(header: &str) -> Option<(usize, usize, String)> {
+        let rest = header.strip_prefix("@@ -")?;
+        let (ranges, section) = rest.split_once(" @@")?;
+        let (old, new) = ranges.split_once(" +")?;
+        let start = |range: &str| range.split(',').next()?.parse().ok();
+        Some((start(old)?, start(new)?, section.to_string()))
+    }
+
+    /// Groups of consecutive changed lines, separated by context lines.
+    fn change_groups(&self) -> Vec<(usize, usize)> {
+        let mut groups = Vec::new();
+        let mut current: Option<(usize, usize)> = None;
+        for (idx, line) in self.lines.iter().enumerate() {
+            let is_change = line.starts_with('+') || line.starts_with('-');
+            let is_marker = line.starts_with('\\');
+            if let Some((_, ref mut end)) = current {
+                if is_change || is_marker {
+                    *end = idx + 1;
+                } else {
+                    groups.extend(current.take());
+                }
+            } else if is_change {
+                current = Some((idx, idx + 1));
+            }
+        }
+        groups.extend(current);
+        groups
+    }
+}
+
+struct FilePatch {
+    header: Vec<String>,
+    hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    fn path(&self) -> &str {
+        self.header
+            .iter()
+            .find_map(|line| line.strip_prefix("+++ b/"))
+            .or_else(|| {
+                self.header
+                    .iter()
+                    .find_map(|line| line.strip_prefix("--- a/"))
+            })
+            .unwrap_or("<unknown>")
+    }
+}
+
+/// A reviewable slice of a hunk: the whole hunk or one split change group.
+struct Piece {
+    hunk: usize,
+    start: usize,
+    end: usize,
+    splittable: bool,
+}
+
+fn parse_patch(diff: &str) -> Vec<FilePatch> {
+    let mut files: Vec<FilePatch> = Vec::new();
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            files.push(FilePatch {
+                header: vec![line.to_string()],
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@ ") {
+            if let Some((old_start, new_start, section)) = Hunk::parse_header(line) {
+                file.hunks.push(Hunk {
+                    old_start,
+                    new_start,
+                    section,
+                    lines: Vec::new(),
+                });
+            }
+        } else if let Some(hunk) = file.hunks.last_mut() {
+            hunk.lines.push(line.to_string());
+        } else {
+            file.header.push(line.to_string());
+        }
+    }
+    files.retain(|f| !f.hunks.is_empty());
+    files
+}
+
+/// Rebuilds `file` as a patch holding only the selected change lines.
+fn build_patch(file: &FilePatch, selected: &[Vec<bool>], reverse: bool) -> Option<String> {
+    let mut out = file.header.join("\n");
+    out.push('\n');
+    let mut delta: isize = 0;
+    let mut any = false;
+
+    for (hunk, keep) in file.hunks.iter().zip(selected) {
+        let (drop_prefix, context_prefix) = if reverse { ('-', '+') } else { ('+', '-') };
+        let mut lines = Vec::new();
+        let mut has_change = false;
+        let mut previous_dropped = false;
+        for (line, &keep_line) in hunk.lines.iter().zip(keep) {
+            if line.starts_with('\\') {
+                if !previous_dropped {
+                    lines.push(line.clone());
+                }
+                continue;
+            }
+            let first = line.chars().next().unwrap_or(' ');
+            previous_dropped = false;
+            if first == ' ' || keep_line {
+                has_change |= first != ' ';
+                lines.push(line.clone());
+            } else if first == context_prefix {
+                lines.push(format!(" {}", &line[1..]));
+            } else if first == drop_prefix {
+                previous_dropped = true;
+            }
+        }
+        if !has_change {
+            continue;
+        }
+        any = true;
+
+        let old_count = lines
+            .iter()
+            .filter(|l| l.starts_with(' ') || l.starts_with('-'))
+            .count();
+        let new_count = lines
+            .iter()
+            .filter(|l| l.starts_with(' ') || l.starts_with('+'))
+            .count();
+        // Applied in reverse, the new side matches the file as it is and the
+        // old side is where it lands once earlier hunks have been undone.
+        let (old_start, new_start) = if reverse {
+            ((hunk.new_start as isize - delta) as usize, hunk.new_start)
+        } else {
+            (hunk.old_start, (hunk.old_start as isize + delta) as usize)
+        };
+        delta += new_count as isize - old_count as isize;
+
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@{}\n",
+            old_start, old_count, new_start, new_count, hunk.section
+        ));
+        for line in lines {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+
+    any.then_some(out)
+}
+
+fn print_piece(file: &FilePatch, piece: &Piece, position: usize, total: usize) {
+    let hunk = &file.hunks[piece.hunk];
+    let from = piece.start.saturating_sub(CONTEXT_LINES);
+    let to = (piece.end + CONTEXT_LINES).min(hunk.lines.len());
+    println!();
+    println!(
+        "\x1b[1m{}\x1b[0m — hunk {}/{} (line {})",
+        file.path(),
+        position,
+        total,
+        hunk.new_start
+    );
+    for line in &hunk.lines[from..to] {
+        match line.chars().next() {
+            Some('+') => println!("\x1b[32m{}\x1b[0m", line),
+            Some('-') => println!("\x1b[31m{}\x1b[0m", line),
+            _ => println!("{}", line),
+        }
+    }
+}
+
+enum Decision {
+    Apply,
+    Skip,
+    Split,
+    SkipFile,
+    Finish,
+    Quit,
+}
+
+fn prompt_decision(action: HunkAction, splittable: bool) -> Result<Decision> {
+    let verb = action.verb();
+    let mut items = vec![
+        (format!("{} this hunk", verb), Decision::Apply),
+        ("Skip this hunk".to_string(), Decision::Skip),
+    ];
+    if splittable {
+        items.push(("Split into smaller hunks".to_string(), Decision::Split));
+    }
+    items.push(("Skip the rest of this file".to_string(), Decision::SkipFile));
+    items.push((
+        format!("{} selected hunks and stop", verb),
+        Decision::Finish,
+    ));
+    items.push(("Quit without changes".to_string(), Decision::Quit));
+
+    let labels: Vec<&str> = items.iter().map(|(label, _)| label.as_str()).collect();
+    let selection = Select::new()
+        .with_prompt(format!("{} this hunk?", verb))
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    Ok(items.swap_remove(selection).1)
+}
+
+/// Applies the hunks of `paths` the user accepts and returns how many.
+pub fn run_hunk_selection(action: HunkAction, paths: &[String]) -> Result<usize> {
+    let repo_root = get_repo_root()?;
+    let mut args = action.diff_args();
+    args.push("--");
+    args.extend(paths.iter().map(String::as_str));
+
+    let output = StdCommand::new("git")
+        .args(&args)
+        .output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
+    }
+
+    let files = parse_patch(&String::from_utf8_lossy(&output.stdout));
+    if files.is_empty() {
+        return Ok(0);
+    }
+
+    let mut patches = Vec::new();
+    let mut accepted = 0;
+    'files: for file in &files {
+        let mut selected: Vec<Vec<bool>> = file
+            .hunks
+            .iter()
+            .map(|h| vec![false; h.lines.len()])
+            .collect();
+        let mut pieces: Vec<Piece> = file
+            .hunks
+            .iter()
+            .enumerate()
+            .map(|(idx, hunk)| Piece {
+                hunk: idx,
+                start: 0,
+                end: hunk.lines.len(),
+                splittable: hunk.change_groups().len() > 1,
+            })
+            .collect();
+        pieces.reverse();
+
+        let mut position = 0;
+        while let Some(piece) = pieces.pop() {
+            position += 1;
+            print_piece(file, &piece, position, position + pieces.len());
+            match prompt_decision(action, piece.splittable)? {
+                Decision::Apply => {
+                    selected[piece.hunk][piece.start..piece.end].fill(true);
+                    accepted += 1;
+                }
+                Decision::Skip => {}
+                Decision::Split => {
+                    position -= 1;
+                    let groups = file.hunks[piece.hunk].change_groups();
+                    for (start, end) in groups.into_iter().rev() {
+                        pieces.push(Piece {
+                            hunk: piece.hunk,
+                            start,
+                            end,
+                            splittable: false,
+                        });
+                    }
+                }
+                Decision::SkipFile => break,
+                Decision::Finish => {
+                    patches.extend(build_patch(file, &selected, action.reverse()));
+                    break 'files;
+                }
+                Decision::Quit => return Ok(0),
+            }
+        }
+        patches.extend(build_patch(file, &selected, action.reverse()));
+    }
+
+    if !patches.is_empty() {
+        journal::record(action.journal_label())?;
+    }
+    for patch in &patches {
+        apply_patch(action, patch, &repo_root)?;
+    }
+    Ok(accepted)
+}
+
+fn apply_patch(action: HunkAction, patch: &str, repo_root: &str) -> Result<()> {
+    let args = action.apply_args();
+    let mut child = StdCommand::new("git")
+        .args(&args)
+        .current_dir(repo_root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(patch.as_bytes())
+            .context("writing patch to git apply")?;
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+    invalidate_porcelain_cache();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/f b/f
+index 1111111..2222222 100644
+--- a/f
++++ b/f
+@@ -1,2 +1,4 @@
+ a
++x
++y
+ b
+@@ -8,2 +10,3 @@
+ h
++z
+ i
+";
+
+    fn patch_for(selected: &[Vec<bool>], reverse: bool) -> Option<String> {
+        let files = parse_patch(DIFF);
+        build_patch(&files[0], selected, reverse)
+    }
+
+    fn hunk_headers(patch: &str) -> Vec<&str> {
+        patch.lines().filter(|l| l.starts_with("@@")).collect()
+    }
+
+    #[test]
+    fn nothing_selected_builds_no_patch() {
+        let none = vec![vec![false; 4], vec![false; 3]];
+        assert_eq!(patch_for(&none, false), None);
+        assert_eq!(patch_for(&none, true), None);
+    }
+
+    #[test]
+    fn forward_patch_drops_rejected_additions() {
+        let selected = vec![vec![false, true, false, false], vec![true; 3]];
+        let patch = patch_for(&selected, false).unwrap();
+        assert_eq!(hunk_headers(&patch), ["@@ -1,2 +1,3 @@", "@@ -8,2 +9,3 @@"]);
+        assert!(!patch.contains("y\n"));
+    }
+
+    #[test]
+    fn reverse_patch_keeps_later_hunks_where_they_are() {
+        let selected = vec![vec![false; 4], vec![true; 3]];
+        let patch = patch_for(&selected, true).unwrap();
+        assert_eq!(hunk_headers(&patch), ["@@ -10,2 +10,3 @@"]);
+    }
+
+    #[test]
+    fn reverse_patch_shifts_old_start_by_undone_lines() {
+        let selected = vec![vec![false, true, false, false], vec![true; 3]];
+        let patch = patch_for(&selected, true).unwrap();
+        assert_eq!(
+            hunk_headers(&patch),
+            ["@@ -1,3 +1,4 @@", "@@ -9,2 +10,3 @@"]
+        );
+        assert!(patch.contains("\n y\n"));
+    }
+}
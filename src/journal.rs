@@ -0,0 +1,310 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command as StdCommand;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use crate::git::{git_stdout, run_git_silent};
+use crate::status::get_git_dir;
+
+const JOURNAL_DIR: &str = "supgit";
+const JOURNAL_FILE: &str = "journal";
+const JOURNAL_REF_PREFIX: &str = "refs/supgit/journal";
+const MAX_JOURNAL_ENTRIES: usize = 50;
+
+pub struct JournalEntry {
+    pub id: u64,
+    pub timestamp: u64,
+    pub operation: String,
+    pub head: Option<String>,
+    pub branch: Option<String>,
+    pub index_tree: Option<String>,
+    pub stash: Option<String>,
+    pub refs: Vec<(String, String)>,
+    /// Refs the operation itself creates, which undo may delete again.
+    pub created: Vec<String>,
+}
+
+impl JournalEntry {
+    fn to_line(&self) -> String {
+        let refs: Vec<String> = self
+            .refs
+            .iter()
+            .map(|(name, sha)| format!("{}:{}", name, sha))
+            .collect();
+        [
+            self.id.to_string(),
+            self.timestamp.to_string(),
+            self.operation.clone(),
+            self.head.clone().unwrap_or_default(),
+            self.branch.clone().unwrap_or_default(),
+            self.index_tree.clone().unwrap_or_default(),
+            self.stash.clone().unwrap_or_default(),
+            refs.join(" "),
+            self.created.join(" "),
+        ]
+        .join("\t")
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        // Entries written before `created` existed have eight fields.
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 8 && fields.len() != 9 {
+            return None;
+        }
+        let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        let refs = fields[7]
+            .split(' ')
+            .filter_map(|pair| pair.split_once(':'))
+            .map(|(name, sha)| (name.to_string(), sha.to_string()))
+            .collect();
+        Some(Self {
+            id: fields[0].parse().ok()?,
+            timestamp: fields[1].parse().ok()?,
+            operation: fields[2].to_string(),
+            head: non_empty(fields[3]),
+            branch: non_empty(fields[4]),
+            index_tree: non_empty(fields[5]),
+            stash: non_empty(fields[6]),
+            refs,
+            created: fields
+                .get(8)
+                .map(|f| f.split(' ').filter(|r| !r.is_empty()))
+                .into_iter()
+                .flatten()
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        let location = match (&self.branch, &self.head) {
+            (Some(branch), Some(head)) => format!("{}@{}", branch, short_sha(head)),
+            (Some(branch), None) => format!("{} (no commits)", branch),
+            (None, Some(head)) => format!("detached@{}", short_sha(head)),
+            (None, None) => "no commits".to_string(),
+        };
+        format!(
+            "#{} {} ({}, {})",
+            self.id,
+            self.operation,
+            location,
+            format_age(self.timestamp)
+        )
+    }
+}
+
+/// Snapshots refs, index and working tree so `supgit undo` can return to
+/// the state before `operation` ran.
+pub fn record(operation: &str) -> Result<()> {
+    record_creating(operation, &[])
+}
+
+/// Like `record`, for operations that create the refs in `created`; undo
+/// deletes those and leaves every other new branch alone.
+pub fn record_creating(operation: &str, created: &[String]) -> Result<()> {
+    let mut entries = load_entries()?;
+    let id = entries.last().map(|e| e.id + 1).unwrap_or(1);
+
+    let head = git_stdout(&["rev-parse", "--verify", "-q", "HEAD"]);
+    let branch = git_stdout(&["symbolic-ref", "--short", "-q", "HEAD"]);
+    let refs = git_stdout(&[
+        "for-each-ref",
+        "--format=%(refname):%(objectname)",
+        "refs/heads",
+    ])
+    .unwrap_or_default()
+    .lines()
+    .filter_map(|line| line.rsplit_once(':'))
+    .map(|(name, sha)| (name.to_string(), sha.to_string()))
+    .collect();
+
+    // write-tree fails while the index has conflicts; such entries can still
+    // restore refs and the working tree snapshot.
+    let index_tree = git_stdout(&["write-tree"]);
+    let stash = git_stdout(&["stash", "create", &format!("supgit journal #{}", id)]);
+
+    if let Some(ref tree) = index_tree {
+        let index_commit = git_stdout(&["commit-tree", tree, "-m", "supgit journal index"]);
+        if let Some(commit) = index_commit {
+            run_git_silent(&[
+                "update-ref",
+                &format!("{}/{}/index", JOURNAL_REF_PREFIX, id),
+                &commit,
+            ])?;
+        }
+    }
+    if let Some(ref stash) = stash {
+        run_git_silent(&[
+            "update-ref",
+            &format!("{}/{}/worktree", JOURNAL_REF_PREFIX, id),
+            stash,
+        ])?;
+    }
+
+    let entry = JournalEntry {
+        id,
+        timestamp: now_secs(),
+        operation: operation.to_string(),
+        head,
+        branch,
+        index_tree,
+        stash,
+        refs,
+        created: created.to_vec(),
+    };
+
+    let path = journal_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    writeln!(file, "{}", entry.to_line())
+        .with_context(|| format!("failed to write to {}", path.display()))?;
+
+    entries.push(entry);
+    if entries.len() > MAX_JOURNAL_ENTRIES {
+        let excess = entries.len() - MAX_JOURNAL_ENTRIES;
+        let pruned: Vec<JournalEntry> = entries.drain(..excess).collect();
+        write_entries(&entries)?;
+        for entry in pruned {
+            delete_entry_refs(entry.id);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn load_entries() -> Result<Vec<JournalEntry>> {
+    let path = journal_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .filter_map(JournalEntry::from_line)
+        .collect())
+}
+
+pub fn pop_entry() -> Result<()> {
+    let mut entries = load_entries()?;
+    if let Some(entry) = entries.pop() {
+        write_entries(&entries)?;
+        delete_entry_refs(entry.id);
+    }
+    Ok(())
+}
+
+/// Puts refs, HEAD, index and tracked files back as captured in `entry`.
+pub fn restore(entry: &JournalEntry) -> Result<()> {
+    // Before the first commit the current branch had no ref yet; going back
+    // there means deleting the ref that commit created.
+    let unborn_branch = match (&entry.branch, &entry.head) {
+        (Some(branch), None) => Some(format!("refs/heads/{}", branch)),
+        _ => None,
+    };
+    let current_refs =
+        git_stdout(&["for-each-ref", "--format=%(refname)", "refs/heads"]).unwrap_or_default();
+    for name in current_refs.lines() {
+        if entry.refs.iter().any(|(recorded, _)| recorded == name) {
+            continue;
+        }
+        if entry.created.iter().any(|created| created == name)
+            || unborn_branch.as_deref() == Some(name)
+        {
+            run_git_silent(&["update-ref", "-d", name])?;
+        } else {
+            println!(
+                "⚠ Keeping branch '{}', which was not created by '{}'",
+                name.trim_start_matches("refs/heads/"),
+                entry.operation
+            );
+        }
+    }
+    for (name, sha) in &entry.refs {
+        run_git_silent(&["update-ref", name, sha])?;
+    }
+
+    match (&entry.branch, &entry.head) {
+        (Some(branch), _) => {
+            run_git_silent(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])?;
+        }
+        (None, Some(head)) => {
+            run_git_silent(&["update-ref", "--no-deref", "HEAD", head])?;
+        }
+        (None, None) => bail!("journal entry #{} has no HEAD to restore", entry.id),
+    }
+
+    // A mixed reset plus checkout never deletes files, so anything added by
+    // the undone operation survives as an untracked file.
+    if let Some(ref head) = entry.head {
+        run_git_silent(&["reset", "-q", head])?;
+    }
+    let worktree = entry.stash.as_ref().or(entry.head.as_ref());
+    if let Some(commit) = worktree
+        && git_stdout(&["ls-tree", "--name-only", commit]).is_some()
+    {
+        run_git_silent(&["checkout", commit, "--", ":/"])?;
+    }
+    if let Some(ref tree) = entry.index_tree {
+        run_git_silent(&["read-tree", tree])?;
+    }
+
+    Ok(())
+}
+
+pub fn format_age(timestamp: u64) -> String {
+    let elapsed = now_secs().saturating_sub(timestamp);
+    match elapsed {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", elapsed / 60),
+        3600..=86_399 => format!("{} h ago", elapsed / 3600),
+        _ => format!("{} d ago", elapsed / 86_400),
+    }
+}
+
+fn write_entries(entries: &[JournalEntry]) -> Result<()> {
+    let path = journal_path()?;
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&entry.to_line());
+        contents.push('\n');
+    }
+    fs::write(&path, contents).with_context(|| format!("failed to write to {}", path.display()))
+}
+
+fn delete_entry_refs(id: u64) {
+    for kind in ["index", "worktree"] {
+        let _ = StdCommand::new("git")
+            .args([
+                "update-ref",
+                "-d",
+                &format!("{}/{}/{}", JOURNAL_REF_PREFIX, id, kind),
+            ])
+            .output();
+    }
+}
+
+fn journal_path() -> Result<PathBuf> {
+    Ok(get_git_dir()?.join(JOURNAL_DIR).join(JOURNAL_FILE))
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,12 @@
+use std::io::{ErrorKind, Write};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    let text = serde_json::to_string_pretty(value).context("failed to serialize JSON output")?;
+    match writeln!(std::io::stdout(), "{}", text) {
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => result.context("failed to write JSON output"),
+    }
+}
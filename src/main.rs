@@ -1,22 +1,55 @@
+mod backup;
+mod binary;
 mod cli;
 mod commands;
+mod conventional;
 mod git;
+mod hunks;
+mod journal;
+mod json;
+mod pager;
+mod policy;
+mod render;
+mod scan;
 mod status;
 
 use anyhow::{Result, bail};
 use clap::Parser;
-use cli::{Cli, SupgitCommand};
+use cli::{Cli, HistoryCommand, SupgitCommand};
 use commands::{
-    check_and_auto_update, create_branch, delete_branch, restore_stage, run_alias,
-    run_branch_interactive, run_clone, run_commit, run_diff, run_pull, run_push, run_reset,
-    run_self_update, run_sync, run_unalias, stage_targets,
+    CommitOptions, DiffOptions, LogOptions, StashOptions, check_and_auto_update, create_branch,
+    delete_branch, print_branches_json, restore_stage, run_alias, run_branch_interactive,
+    run_clone, run_commit, run_diff, run_fixup, run_history_edit, run_log, run_pull, run_push,
+    run_reset, run_restore_backup, run_self_update, run_show, run_stash, run_status, run_sync,
+    run_unalias, run_undo, stage_targets,
 };
-use git::{check_in_repo, run_git, run_git_silent};
+use git::{check_in_repo, run_git_silent};
+use render::RenderOptions;
 use strsim::jaro_winkler;
 
 const COMMANDS: &[&str] = &[
-    "init", "stage", "unstage", "status", "commit", "log", "diff", "reset", "branch", "push",
-    "pull", "sync", "clone", "update", "alias", "unalias",
+    "init",
+    "stage",
+    "unstage",
+    "status",
+    "commit",
+    "fixup",
+    "history",
+    "log",
+    "show",
+    "diff",
+    "reset",
+    "branch",
+    "push",
+    "pull",
+    "sync",
+    "stash",
+    "clone",
+    "update",
+    "undo",
+    "restore-backup",
+    "alias",
+    "unalias",
 ];
 
 fn find_closest_command(input: &str) -> Option<&'static str> {
@@ -66,14 +99,21 @@ fn run() -> Result<()> {
                     new_args.extend(args[pos + 1..].iter().cloned());
                 }
                 let cli = Cli::parse_from(&new_args);
+                if cli.no_pager {
+                    pager::disable();
+                }
                 if let Some(command) = cli.command {
-                    return execute_command(command);
+                    return execute_command(command, cli.json);
                 }
             }
             err.exit();
         }
     };
 
+    if cli.no_pager {
+        pager::disable();
+    }
+
     if cli.explain {
         print_explanations();
         return Ok(());
@@ -84,10 +124,10 @@ fn run() -> Result<()> {
         None => bail!("'supgit' requires a subcommand; use --help to see the available list"),
     };
 
-    execute_command(command)
+    execute_command(command, cli.json)
 }
 
-fn execute_command(command: SupgitCommand) -> Result<()> {
+fn execute_command(command: SupgitCommand, json: bool) -> Result<()> {
     if !matches!(
         command,
         SupgitCommand::Init
@@ -108,24 +148,72 @@ fn execute_command(command: SupgitCommand) -> Result<()> {
             targets,
             all,
             tracked,
-        } => stage_targets(&targets, all, tracked)?,
-        SupgitCommand::Unstage { targets, all } => restore_stage(&targets, all)?,
+            patch,
+        } => stage_targets(&targets, all, tracked, patch)?,
+        SupgitCommand::Unstage {
+            targets,
+            all,
+            patch,
+        } => restore_stage(&targets, all, patch)?,
         SupgitCommand::Status { short } => {
-            if short {
-                run_git(&["status", "-sb"])?;
-            } else {
-                run_git(&["status"])?;
-            }
+            run_status(short, json)?;
         }
-        SupgitCommand::Log { short } => {
-            if short {
-                run_git(&["log", "--oneline", "--decorate", "-n", "20"])?;
-            } else {
-                run_git(&["log", "--decorate", "-n", "40"])?;
-            }
+        SupgitCommand::Log {
+            short,
+            limit,
+            author,
+            since,
+            until,
+            path,
+            grep,
+            graph,
+            interactive,
+        } => {
+            run_log(
+                LogOptions {
+                    short,
+                    limit,
+                    author,
+                    since,
+                    until,
+                    path,
+                    grep,
+                    graph,
+                    interactive,
+                },
+                json,
+            )?;
         }
-        SupgitCommand::Diff { path, staged } => {
-            run_diff(path, staged)?;
+        SupgitCommand::Show { commit, path } => {
+            run_show(commit, path, json)?;
+        }
+        SupgitCommand::Diff {
+            path,
+            staged,
+            from,
+            to,
+            stat,
+            side_by_side,
+            word_diff,
+            line_numbers,
+            collapse_whitespace,
+        } => {
+            run_diff(
+                DiffOptions {
+                    path,
+                    staged,
+                    from,
+                    to,
+                    stat,
+                    render: RenderOptions {
+                        side_by_side,
+                        word_diff,
+                        line_numbers,
+                        collapse_whitespace,
+                    },
+                },
+                json,
+            )?;
         }
         SupgitCommand::Reset {
             all,
@@ -133,24 +221,35 @@ fn execute_command(command: SupgitCommand) -> Result<()> {
             unstaged,
             tracked,
             untracked,
-        } => run_reset(all, staged, unstaged, tracked, untracked)?,
+            patch,
+        } => run_reset(all, staged, unstaged, tracked, untracked, patch)?,
         SupgitCommand::Branch { create, delete } => {
             if let Some(branch_name) = create {
                 create_branch(&branch_name)?;
             } else if let Some(branch_name) = delete {
                 delete_branch(&branch_name)?;
+            } else if json {
+                print_branches_json()?;
             } else {
                 run_branch_interactive()?;
             }
         }
-        SupgitCommand::Push { remote, branch } => {
-            run_push(remote, branch)?;
+        SupgitCommand::Push {
+            remote,
+            branch,
+            allow_secrets,
+        } => {
+            run_push(remote, branch, allow_secrets)?;
         }
         SupgitCommand::Pull { remote, branch } => {
             run_pull(remote, branch)?;
         }
-        SupgitCommand::Sync { remote, branch } => {
-            run_sync(remote.as_deref(), branch.as_deref())?;
+        SupgitCommand::Sync {
+            remote,
+            branch,
+            allow_secrets,
+        } => {
+            run_sync(remote.as_deref(), branch.as_deref(), allow_secrets)?;
         }
         SupgitCommand::Commit {
             message,
@@ -160,8 +259,62 @@ fn execute_command(command: SupgitCommand) -> Result<()> {
             push,
             amend,
             no_verify,
+            conventional,
+            edit,
+            yes,
+            allow_secrets,
+            force_amend,
         } => {
-            run_commit(message, all, staged, unstaged, push, amend, no_verify)?;
+            run_commit(CommitOptions {
+                message,
+                all,
+                staged,
+                unstaged,
+                push,
+                amend,
+                no_verify,
+                conventional,
+                edit,
+                yes,
+                allow_secrets,
+                force_amend,
+            })?;
+        }
+        SupgitCommand::Stash {
+            paths,
+            save,
+            message,
+            untracked,
+            list,
+            show,
+            apply,
+            pop,
+            drop,
+            clear,
+        } => {
+            run_stash(StashOptions {
+                save,
+                message,
+                untracked,
+                paths,
+                list,
+                show,
+                apply,
+                pop,
+                drop,
+                clear,
+            })?;
+        }
+        SupgitCommand::Fixup {
+            commit,
+            allow_secrets,
+        } => {
+            run_fixup(commit, allow_secrets)?;
+        }
+        SupgitCommand::History {
+            command: HistoryCommand::Edit { count, force },
+        } => {
+            run_history_edit(count, force)?;
         }
         SupgitCommand::Clone { url, directory } => {
             run_clone(&url, directory.as_deref())?;
@@ -169,6 +322,12 @@ fn execute_command(command: SupgitCommand) -> Result<()> {
         SupgitCommand::Update => {
             run_self_update(None)?;
         }
+        SupgitCommand::Undo { list } => {
+            run_undo(list)?;
+        }
+        SupgitCommand::RestoreBackup { id, list } => {
+            run_restore_backup(id, list)?;
+        }
         SupgitCommand::Alias { dry_run, git, sg } => {
             run_alias(dry_run, git, sg)?;
         }
@@ -184,27 +343,62 @@ fn print_explanations() {
     println!("SupGIT simplifies Git for beginners by wrapping each major workflow:");
     println!();
     println!("  init    – initialize a Git repository (runs `git init`).");
-    println!("  stage   – add files to the staging area (interactive, or use --all/--tracked).");
-    println!("  unstage – remove staged files safely (interactive, or use --all).");
-    println!("  status  – show what is staged vs unstaged (`--short` uses `git status -sb`).");
-    println!("  log     – view history (`--short` shows compact entries).");
-    println!("  diff    – compare working changes (`--staged` shows what will be committed).");
+    println!(
+        "  stage   – add files to the staging area (interactive, or use --all/--tracked; --patch picks individual hunks)."
+    );
+    println!(
+        "  unstage – remove staged files safely (interactive, or use --all; --patch unstages individual hunks)."
+    );
+    println!(
+        "  status  – show staged, unstaged, untracked and conflicted files plus the next step (`--short` is compact)."
+    );
+    println!(
+        "  log     – view history with per-commit stats; filter with `--limit`, `--author`, `--since`/`--until`, `--path` and `--grep`, draw branches with `--graph`, or browse commits and their diffs with `--interactive`."
+    );
+    println!(
+        "  show    – inspect a commit (HEAD by default): message, changed files with +/- counts, then pick a file to see its patch."
+    );
+    println!(
+        "  diff    – review changes file by file (`--staged` for what will be committed, `diff <branch>` or `--from`/`--to` to compare revisions, `--stat` for a summary)."
+    );
     println!(
         "  branch  – list and checkout branches (interactive); use -c <name> to create, -d <name> to delete a branch."
     );
     println!(
-        "  reset   – discard changes (interactive, or use --all/--staged/--unstaged/--tracked/--untracked)."
+        "  reset   – discard changes (interactive, or use --all/--staged/--unstaged/--tracked/--untracked; --patch discards individual hunks)."
     );
     println!(
-        "  push    – send commits to your remote (uses Git's defaults unless you pass `--remote`/`--branch`)."
+        "  push    – send commits to your remote (uses Git's defaults unless you pass `--remote`/`--branch`); commit, push and sync stop on secrets, .env files and oversized files unless you pass `--allow-secrets`."
     );
     println!("  pull    – fetch + merge from your remote repository.");
     println!(
-        "  commit  – make commits; `--all` stages everything, `--unstaged` stages only modified tracked files, `--push` runs `git push`, `--amend` rewrites the last commit, and `--no-verify` skips hooks."
+        "  commit  – make commits; `--all` stages everything, `--unstaged` stages only modified tracked files, `--push` runs `git push`, `--amend` rewrites the last commit (asking first if it is already pushed; `--force-amend` skips that), `--no-verify` skips hooks, `--conventional` guides you through a Conventional Commits message, `--edit` writes subject and body in $EDITOR, and `--yes` skips the review screen shown before committing."
+    );
+    println!(
+        "  fixup   – fold staged changes into an earlier unpushed commit (pick it from a list, or pass its hash)."
+    );
+    println!(
+        "  history – `history edit` reorders, rewords, squashes, drops or splits unpushed commits (`--force` includes pushed ones)."
     );
     println!("  sync    – fetch, pull, and push in one command with graceful error handling.");
+    println!(
+        "  stash   – shelve changes (interactive, or -m/--untracked/paths to save; --list/--show/--apply/--pop/--drop/--clear)."
+    );
     println!("  clone   – clone a repository and automatically change into it.");
+    println!(
+        "  undo    – reverse the last SupGIT stage/unstage/commit/reset/branch/sync; `--list` shows the journal."
+    );
+    println!(
+        "  restore-backup – bring back files saved before a destructive reset; `--list` shows backups."
+    );
     println!("  alias   – add alias (--git or --sg, or shows selector).");
     println!("  unalias – remove alias (--git or --sg, or shows selector).");
     println!("  update  – update supgit to the latest version via cargo.");
+    println!();
+    println!(
+        "Pass `--json` to status, branch, diff, log or show to get machine-readable output for scripts and editors."
+    );
+    println!(
+        "Long diff and log output goes through your pager (GIT_PAGER, core.pager, PAGER, then less) when printing to a terminal; pass `--no-pager` to print directly."
+    );
 }
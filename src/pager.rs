@@ -0,0 +1,70 @@
+//! Sends long output through the user's pager the way git does, so diffs
+//! and logs that SupGIT captures still scroll instead of flooding the
+//! terminal.
+
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::process::{Command as StdCommand, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+
+use crate::git::git_stdout;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns paging off for the rest of the process (`--no-pager`).
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Prints `text`, through the pager when stdout is a terminal.
+pub fn page(text: &str) -> Result<()> {
+    let Some(pager) = resolve_pager() else {
+        print!("{}", text);
+        return Ok(());
+    };
+
+    let mut command = StdCommand::new("sh");
+    command.arg("-c").arg(&pager).stdin(Stdio::piped());
+    // Same defaults git gives less: quit if one screen, keep colors, don't
+    // clear the screen on exit.
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    if std::env::var_os("LV").is_none() {
+        command.env("LV", "-c");
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(_) => {
+            print!("{}", text);
+            return Ok(());
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        match stdin.write_all(text.as_bytes()) {
+            // The user quit the pager before reading everything.
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => {}
+            result => result.context("writing to the pager")?,
+        }
+    }
+    child
+        .wait()
+        .with_context(|| format!("waiting for pager '{}'", pager))?;
+    Ok(())
+}
+
+/// The pager git would use, or `None` when output should go straight to stdout.
+fn resolve_pager() -> Option<String> {
+    // Pager commands are run through `sh`.
+    if !cfg!(unix) || DISABLED.load(Ordering::Relaxed) || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let pager = git_stdout(&["var", "GIT_PAGER"]).unwrap_or_else(|| "less".to_string());
+    let pager = pager.trim();
+    if pager.is_empty() || pager == "cat" {
+        return None;
+    }
+    Some(pager.to_string())
+}
@@ -0,0 +1,287 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Once;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::status::get_repo_root;
+
+const POLICY_FILE: &str = ".supgit.toml";
+
+/// Keys this version understands, per table; others are warned about once.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    (
+        "commit",
+        &[
+            "max-subject-length",
+            "max-body-line-length",
+            "ticket-pattern",
+            "forbidden-words",
+            "blank-line-after-subject",
+            "template",
+        ],
+    ),
+    ("guard", &["max-file-size-mb"]),
+];
+
+static UNKNOWN_KEYS_WARNING: Once = Once::new();
+
+#[derive(Deserialize, Default)]
+struct PolicyFile {
+    #[serde(default)]
+    commit: CommitPolicy,
+    #[serde(default)]
+    guard: GuardPolicy,
+}
+
+impl PolicyFile {
+    /// Reads `.supgit.toml` at the repository root, or the defaults if the
+    /// file does not exist.
+    fn load() -> Result<Self> {
+        let path = Path::new(&get_repo_root()?).join(POLICY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let table: toml::Table =
+            toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))?;
+        UNKNOWN_KEYS_WARNING.call_once(|| warn_unknown_keys(&table));
+        let file: PolicyFile = table
+            .try_into()
+            .with_context(|| format!("parsing {}", path.display()))?;
+        if let Some(ref pattern) = file.commit.ticket_pattern {
+            Regex::new(pattern)
+                .with_context(|| format!("invalid ticket-pattern in {}", path.display()))?;
+        }
+        Ok(file)
+    }
+}
+
+fn warn_unknown_keys(table: &toml::Table) {
+    for (name, value) in table {
+        let Some((_, known)) = KNOWN_KEYS.iter().find(|(table, _)| table == name) else {
+            eprintln!("⚠ Ignoring unknown table [{}] in {}", name, POLICY_FILE);
+            continue;
+        };
+        let Some(keys) = value.as_table() else {
+            continue;
+        };
+        for key in keys.keys().filter(|key| !known.contains(&key.as_str())) {
+            eprintln!(
+                "⚠ Ignoring unknown key '{}' in [{}] of {}",
+                key, name, POLICY_FILE
+            );
+        }
+    }
+}
+
+/// The `[guard]` table of `.supgit.toml`, used by the secret and
+/// large-file scanner.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GuardPolicy {
+    pub max_file_size_mb: f64,
+}
+
+impl Default for GuardPolicy {
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: 5.0,
+        }
+    }
+}
+
+impl GuardPolicy {
+    pub fn load() -> Result<Self> {
+        Ok(PolicyFile::load()?.guard)
+    }
+
+    pub fn max_file_size(&self) -> u64 {
+        (self.max_file_size_mb * 1024.0 * 1024.0) as u64
+    }
+}
+
+/// The `[commit]` table of `.supgit.toml`.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CommitPolicy {
+    pub max_subject_length: Option<usize>,
+    pub max_body_line_length: Option<usize>,
+    pub ticket_pattern: Option<String>,
+    pub forbidden_words: Vec<String>,
+    pub blank_line_after_subject: bool,
+    pub template: Option<String>,
+}
+
+impl Default for CommitPolicy {
+    fn default() -> Self {
+        Self {
+            max_subject_length: None,
+            max_body_line_length: None,
+            ticket_pattern: None,
+            forbidden_words: Vec::new(),
+            blank_line_after_subject: true,
+            template: None,
+        }
+    }
+}
+
+impl CommitPolicy {
+    pub fn load() -> Result<Self> {
+        Ok(PolicyFile::load()?.commit)
+    }
+
+    /// The configured template with comment lines removed, used to pre-fill
+    /// the commit message.
+    pub fn template_text(&self) -> Result<Option<String>> {
+        let Some(ref template) = self.template else {
+            return Ok(None);
+        };
+        let path = Path::new(&get_repo_root()?).join(template);
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("reading commit template {}", path.display()))?;
+        let text = strip_comments(&text);
+        Ok((!text.is_empty()).then_some(text))
+    }
+
+    /// Returns every rule `message` breaks, phrased as what to change.
+    pub fn lint(&self, message: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        let lines: Vec<&str> = message.lines().collect();
+        let subject = lines.first().copied().unwrap_or_default();
+
+        if let Some(max) = self.max_subject_length {
+            let len = subject.chars().count();
+            if len > max {
+                problems.push(format!(
+                    "shorten the subject line to {} characters or fewer (it has {})",
+                    max, len
+                ));
+            }
+        }
+
+        if let Some(ref pattern) = self.ticket_pattern
+            && let Ok(regex) = Regex::new(pattern)
+            && !regex.is_match(subject)
+        {
+            problems.push(format!(
+                "add a ticket reference to the subject matching `{}`",
+                pattern
+            ));
+        }
+
+        let lowered = message.to_lowercase();
+        let words: Vec<&str> = lowered
+            .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+            .collect();
+        for forbidden in &self.forbidden_words {
+            if words.contains(&forbidden.to_lowercase().as_str()) {
+                problems.push(format!(
+                    "remove '{}' from the message; it is listed in forbidden-words",
+                    forbidden
+                ));
+            }
+        }
+
+        if self.blank_line_after_subject && lines.len() > 1 && !lines[1].trim().is_empty() {
+            problems.push("leave a blank line between the subject and the body".to_string());
+        }
+
+        if let Some(max) = self.max_body_line_length {
+            for (idx, line) in lines.iter().enumerate().skip(1) {
+                let len = line.chars().count();
+                if len > max {
+                    problems.push(format!(
+                        "wrap body line {} to {} characters or fewer (it has {})",
+                        idx + 1,
+                        max,
+                        len
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// Drops `#` comment lines and surrounding blank lines, the way `git commit`
+/// cleans up an edited message.
+pub fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_toml(toml: &str) -> CommitPolicy {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn default_policy_only_wants_a_blank_line_after_the_subject() {
+        let policy = CommitPolicy::default();
+        assert!(policy.lint("").is_empty());
+        assert!(policy.lint(&"x".repeat(200)).is_empty());
+        assert!(policy.lint("subject\n\nbody").is_empty());
+        assert_eq!(
+            policy.lint("subject\nbody"),
+            ["leave a blank line between the subject and the body"]
+        );
+        assert!(
+            from_toml("blank-line-after-subject = false")
+                .lint("subject\nbody")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        let policy = from_toml("max-subject-length = 5\nmax-body-line-length = 4");
+        assert!(policy.lint("héllo\n\nñañá").is_empty());
+        assert_eq!(
+            policy.lint("héllo!\n\nok\nñañáñ"),
+            [
+                "shorten the subject line to 5 characters or fewer (it has 6)",
+                "wrap body line 4 to 4 characters or fewer (it has 5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_ticket_pattern_on_the_subject_only() {
+        let policy = from_toml(r#"ticket-pattern = '[A-Z]+-\d+'"#);
+        assert!(policy.lint("PROJ-12 fix login").is_empty());
+        assert_eq!(policy.lint("fix login\n\nPROJ-12").len(), 1);
+        // An invalid pattern is rejected when the file loads, not here.
+        let invalid = CommitPolicy {
+            ticket_pattern: Some("(".to_string()),
+            ..CommitPolicy::default()
+        };
+        assert!(invalid.lint("x").is_empty());
+    }
+
+    #[test]
+    fn matches_forbidden_words_whole_and_case_insensitively() {
+        let policy = from_toml(r#"forbidden-words = ["WIP", "fixup"]"#);
+        assert_eq!(
+            policy.lint("wip: half done"),
+            ["remove 'WIP' from the message; it is listed in forbidden-words"]
+        );
+        assert!(
+            policy
+                .lint("wipe the cache\n\nnot a fixup-commit")
+                .is_empty()
+        );
+        assert_eq!(policy.lint("Fixup\n\nstill WIP").len(), 2);
+    }
+}
@@ -0,0 +1,423 @@
+//! SupGIT's own rendering of unified diffs: line numbers, word-level
+//! highlights, side-by-side columns and collapsed whitespace-only changes.
+
+use dialoguer::console::Term;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const EMPHASIS: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+const TAB: &str = "    ";
+/// Lines with more tokens than this are marked whole instead of word by word.
+const MAX_WORD_DIFF_TOKENS: usize = 400;
+
+#[derive(Clone, Copy, Default)]
+pub struct RenderOptions {
+    pub side_by_side: bool,
+    pub word_diff: bool,
+    pub line_numbers: bool,
+    pub collapse_whitespace: bool,
+}
+
+impl RenderOptions {
+    /// True when any option asks for SupGIT's renderer instead of git's colors.
+    pub fn is_custom(&self) -> bool {
+        self.side_by_side || self.word_diff || self.line_numbers || self.collapse_whitespace
+    }
+}
+
+/// A piece of a line and whether it differs from the other side.
+type Token = (String, bool);
+
+struct Block {
+    removed: Vec<String>,
+    added: Vec<String>,
+}
+
+struct Renderer {
+    options: RenderOptions,
+    column: usize,
+    old_line: usize,
+    new_line: usize,
+    out: String,
+}
+
+/// Renders uncolored `git diff` output.
+pub fn render_diff(diff: &str, options: RenderOptions) -> String {
+    let width = Term::stdout()
+        .size_checked()
+        .map(|(_, cols)| cols as usize)
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(120);
+    // Two line-number gutters plus the separator take 15 columns.
+    let column = (width.saturating_sub(15) / 2).max(20);
+
+    let mut renderer = Renderer {
+        options,
+        column,
+        old_line: 0,
+        new_line: 0,
+        out: String::new(),
+    };
+    let mut block = Block {
+        removed: Vec::new(),
+        added: Vec::new(),
+    };
+    let mut in_hunk = false;
+
+    for raw in diff.lines() {
+        let line = raw.replace('\t', TAB);
+        if line.starts_with("diff --git ") {
+            renderer.flush(&mut block);
+            in_hunk = false;
+            renderer
+                .out
+                .push_str(&format!("{}{}{}\n", BOLD, line, RESET));
+            continue;
+        }
+        if line.starts_with("@@ ") {
+            renderer.flush(&mut block);
+            in_hunk = true;
+            renderer.start_hunk(&line);
+            continue;
+        }
+        if !in_hunk {
+            renderer
+                .out
+                .push_str(&format!("{}{}{}\n", BOLD, line, RESET));
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('-') {
+            if !block.added.is_empty() {
+                renderer.flush(&mut block);
+            }
+            block.removed.push(rest.to_string());
+        } else if let Some(rest) = line.strip_prefix('+') {
+            block.added.push(rest.to_string());
+        } else if line.starts_with('\\') {
+            renderer.flush(&mut block);
+            renderer
+                .out
+                .push_str(&format!("{}{}{}\n", DIM, line, RESET));
+        } else {
+            renderer.flush(&mut block);
+            renderer.context(line.strip_prefix(' ').unwrap_or(&line));
+        }
+    }
+    renderer.flush(&mut block);
+    renderer.out
+}
+
+impl Renderer {
+    fn start_hunk(&mut self, header: &str) {
+        let parse = |range: &str| -> usize {
+            range
+                .split(',')
+                .next()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1)
+        };
+        if let Some(rest) = header.strip_prefix("@@ -")
+            && let Some((ranges, _)) = rest.split_once(" @@")
+            && let Some((old, new)) = ranges.split_once(" +")
+        {
+            self.old_line = parse(old);
+            self.new_line = parse(new);
+        }
+        self.out.push_str(&format!("{}{}{}\n", CYAN, header, RESET));
+    }
+
+    fn context(&mut self, text: &str) {
+        let plain = vec![(text.to_string(), false)];
+        if self.options.side_by_side {
+            self.side_by_side_row(
+                Some((self.old_line, &plain)),
+                Some((self.new_line, &plain)),
+                "",
+                "",
+            );
+        } else {
+            let gutter = self.gutter(Some(self.old_line), Some(self.new_line));
+            self.out.push_str(&format!("{} {}\n", gutter, text));
+        }
+        self.old_line += 1;
+        self.new_line += 1;
+    }
+
+    fn flush(&mut self, block: &mut Block) {
+        if block.removed.is_empty() && block.added.is_empty() {
+            return;
+        }
+        let removed = std::mem::take(&mut block.removed);
+        let added = std::mem::take(&mut block.added);
+
+        if self.options.collapse_whitespace && is_whitespace_only(&removed, &added) {
+            let count = removed.len().max(added.len());
+            let gutter = self.gutter(None, None);
+            self.out.push_str(&format!(
+                "{}{} ~ {} whitespace-only line(s) changed{}\n",
+                DIM, gutter, count, RESET
+            ));
+            self.old_line += removed.len();
+            self.new_line += added.len();
+            return;
+        }
+
+        let paired = if self.options.word_diff {
+            removed.len().min(added.len())
+        } else {
+            0
+        };
+        let mut old_tokens: Vec<Vec<Token>> = Vec::new();
+        let mut new_tokens: Vec<Vec<Token>> = Vec::new();
+        for idx in 0..removed.len().max(added.len()) {
+            if idx < paired {
+                let (old, new) = word_diff(&removed[idx], &added[idx]);
+                old_tokens.push(old);
+                new_tokens.push(new);
+            } else {
+                if let Some(line) = removed.get(idx) {
+                    old_tokens.push(vec![(line.clone(), false)]);
+                }
+                if let Some(line) = added.get(idx) {
+                    new_tokens.push(vec![(line.clone(), false)]);
+                }
+            }
+        }
+
+        if self.options.side_by_side {
+            for idx in 0..old_tokens.len().max(new_tokens.len()) {
+                let left = old_tokens.get(idx).map(|t| (self.old_line + idx, t));
+                let right = new_tokens.get(idx).map(|t| (self.new_line + idx, t));
+                self.side_by_side_row(left, right, RED, GREEN);
+            }
+        } else {
+            for (idx, tokens) in old_tokens.iter().enumerate() {
+                let gutter = self.gutter(Some(self.old_line + idx), None);
+                let text = paint(tokens, RED, None);
+                self.out
+                    .push_str(&format!("{}{}-{}{}\n", gutter, RED, text, RESET));
+            }
+            for (idx, tokens) in new_tokens.iter().enumerate() {
+                let gutter = self.gutter(None, Some(self.new_line + idx));
+                let text = paint(tokens, GREEN, None);
+                self.out
+                    .push_str(&format!("{}{}+{}{}\n", gutter, GREEN, text, RESET));
+            }
+        }
+        self.old_line += removed.len();
+        self.new_line += added.len();
+    }
+
+    fn gutter(&self, old: Option<usize>, new: Option<usize>) -> String {
+        if !self.options.line_numbers {
+            return String::new();
+        }
+        let number = |n: Option<usize>| n.map_or(String::new(), |n| n.to_string());
+        format!("{}{:>5} {:>5} │{}", DIM, number(old), number(new), RESET)
+    }
+
+    fn side_by_side_row(
+        &mut self,
+        left: Option<(usize, &Vec<Token>)>,
+        right: Option<(usize, &Vec<Token>)>,
+        left_color: &str,
+        right_color: &str,
+    ) {
+        let column = self.column;
+        let cell = |side: Option<(usize, &Vec<Token>)>, color: &str| -> String {
+            match side {
+                Some((number, tokens)) => {
+                    let text = paint(tokens, color, Some(column));
+                    let visible: usize = tokens.iter().map(|(t, _)| t.chars().count()).sum();
+                    let padding = column.saturating_sub(visible);
+                    format!(
+                        "{}{:>5}{} {}{}{}{}",
+                        DIM,
+                        number,
+                        RESET,
+                        color,
+                        text,
+                        RESET,
+                        " ".repeat(padding)
+                    )
+                }
+                None => " ".repeat(column + 6),
+            }
+        };
+        let left_cell = cell(left, left_color);
+        let right_cell = cell(right, right_color);
+        self.out.push_str(&format!(
+            "{} {}│{} {}\n",
+            left_cell,
+            DIM,
+            RESET,
+            right_cell.trim_end()
+        ));
+    }
+}
+
+/// Whether the removed and added lines only differ in whitespace or blank lines.
+fn is_whitespace_only(removed: &[String], added: &[String]) -> bool {
+    let squash = |line: &String| -> String { line.split_whitespace().collect() };
+    let old: Vec<String> = removed
+        .iter()
+        .map(squash)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let new: Vec<String> = added.iter().map(squash).filter(|l| !l.is_empty()).collect();
+    old == new
+}
+
+/// Splits a line into words, runs of whitespace and single punctuation
+/// characters so highlights land on whole identifiers.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut prev: Option<u8> = None;
+    for c in line.chars() {
+        let current = class(c);
+        match tokens.last_mut() {
+            Some(last) if prev == Some(current) && current != 2 => last.push(c),
+            _ => tokens.push(c.to_string()),
+        }
+        prev = Some(current);
+    }
+    tokens
+}
+
+/// Marks the tokens that are not part of the longest common subsequence of
+/// the two lines.
+fn word_diff(old: &str, new: &str) -> (Vec<Token>, Vec<Token>) {
+    let a = tokenize(old);
+    let b = tokenize(new);
+    if a.len() > MAX_WORD_DIFF_TOKENS || b.len() > MAX_WORD_DIFF_TOKENS {
+        return (vec![(old.to_string(), true)], vec![(new.to_string(), true)]);
+    }
+
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut old_tokens = Vec::new();
+    let mut new_tokens = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            old_tokens.push((a[i].clone(), false));
+            new_tokens.push((b[j].clone(), false));
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            new_tokens.push((b[j].clone(), true));
+            j += 1;
+        } else {
+            old_tokens.push((a[i].clone(), true));
+            i += 1;
+        }
+    }
+    (old_tokens, new_tokens)
+}
+
+/// Colors a line, emphasising changed tokens, and cuts it to `limit`
+/// visible characters (ending in `…`) when given.
+fn paint(tokens: &[Token], color: &str, limit: Option<usize>) -> String {
+    let total: usize = tokens.iter().map(|(t, _)| t.chars().count()).sum();
+    let mut budget = match limit {
+        Some(limit) if total > limit => limit.saturating_sub(1),
+        _ => usize::MAX,
+    };
+    let mut out = String::new();
+    for (text, changed) in tokens {
+        if budget == 0 {
+            break;
+        }
+        let piece: String = text.chars().take(budget).collect();
+        budget = budget.saturating_sub(piece.chars().count());
+        if *changed {
+            out.push_str(&format!("{}{}{}{}", EMPHASIS, piece, RESET, color));
+        } else {
+            out.push_str(&piece);
+        }
+    }
+    if total > limit.unwrap_or(usize::MAX) {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(tokens: &[Token]) -> Vec<&str> {
+        tokens
+            .iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(text, _)| text.as_str())
+            .collect()
+    }
+
+    fn joined(tokens: &[Token]) -> String {
+        tokens.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    #[test]
+    fn tokenize_splits_words_spaces_and_punctuation() {
+        assert_eq!(
+            tokenize("let foo_bar  = baz(1, 2);"),
+            [
+                "let", " ", "foo_bar", "  ", "=", " ", "baz", "(", "1", ",", " ", "2", ")", ";"
+            ]
+        );
+        assert_eq!(tokenize("héllo wörld"), ["héllo", " ", "wörld"]);
+        assert_eq!(tokenize("->"), ["-", ">"]);
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn word_diff_marks_only_changed_tokens() {
+        let (old, new) = word_diff("let total = price * count;", "let total = price * amount;");
+        assert_eq!(changed(&old), ["count"]);
+        assert_eq!(changed(&new), ["amount"]);
+        assert_eq!(joined(&old), "let total = price * count;");
+        assert_eq!(joined(&new), "let total = price * amount;");
+    }
+
+    #[test]
+    fn word_diff_handles_insertions_and_identical_lines() {
+        let (old, new) = word_diff("call(a)", "call(a, b)");
+        assert!(changed(&old).is_empty());
+        assert_eq!(changed(&new), [",", " ", "b"]);
+
+        let (old, new) = word_diff("same", "same");
+        assert!(changed(&old).is_empty() && changed(&new).is_empty());
+    }
+
+    #[test]
+    fn word_diff_marks_whole_lines_past_the_token_limit() {
+        let long = "a ".repeat(MAX_WORD_DIFF_TOKENS);
+        let (old, new) = word_diff(&long, "a");
+        assert_eq!(old, [(long.clone(), true)]);
+        assert_eq!(new, [("a".to_string(), true)]);
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command as StdCommand, Stdio};
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+use crate::policy::GuardPolicy;
+
+/// Files above this size get a warning even when they are under the
+/// blocking limit from `.supgit.toml`.
+const LARGE_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Minimum length and Shannon entropy (bits per character) for a token to
+/// be reported as a likely random secret.
+const ENTROPY_MIN_LENGTH: usize = 32;
+const ENTROPY_THRESHOLD: f64 = 4.5;
+
+const SECRET_PATTERNS: &[(&str, &str)] = &[
+    ("AWS access key", r"\b(AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("private key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+    ("GitHub token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("Slack token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    (
+        "hard-coded credential",
+        r#"(?i)\b(api[_-]?key|secret|password|passwd|token)\b\s*[:=]\s*["'][^"'\s]{8,}["']"#,
+    ),
+];
+
+/// Lock files are full of integrity hashes that look random by design.
+const ENTROPY_SKIPPED_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+
+pub struct Finding {
+    pub path: String,
+    pub line: Option<usize>,
+    pub message: String,
+    /// Blocking findings stop the commit or push unless `--allow-secrets`
+    /// is passed; the rest are only warnings.
+    pub blocking: bool,
+}
+
+impl Finding {
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.path, line),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// Scans the staged changes for conflict markers, secrets, `.env` files and
+/// oversized files.
+pub fn scan_staged() -> Result<Vec<Finding>> {
+    let policy = GuardPolicy::load()?;
+    let diff = git_output(&[
+        "diff",
+        "--cached",
+        "-U0",
+        "--no-color",
+        "--no-ext-diff",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ])?;
+    let mut findings = scan_added_lines(&diff);
+
+    let names = git_output(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--no-renames",
+        "--diff-filter=d",
+    ])?;
+    let paths: Vec<&str> = names.split('\0').filter(|p| !p.is_empty()).collect();
+    let objects: Vec<String> = paths.iter().map(|p| format!(":{}", p)).collect();
+    let sizes = blob_sizes(&objects)?;
+    for (path, size) in paths.iter().zip(sizes) {
+        findings.extend(check_file(path, size, &policy));
+    }
+    Ok(findings)
+}
+
+/// Scans every commit that `git push` would send for `rev` (commits not yet
+/// on any remote-tracking branch).
+pub fn scan_outgoing(rev: &str) -> Result<Vec<Finding>> {
+    let policy = GuardPolicy::load()?;
+    let diff = git_output(&[
+        "log",
+        "--format=",
+        "-p",
+        "-U0",
+        "--no-color",
+        "--no-ext-diff",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        rev,
+        "--not",
+        "--remotes",
+    ])?;
+    let mut findings = scan_added_lines(&diff);
+
+    let objects = git_output(&["rev-list", "--objects", rev, "--not", "--remotes"])?;
+    let mut blobs: HashMap<String, String> = HashMap::new();
+    for line in objects.lines() {
+        if let Some((sha, path)) = line.split_once(' ') {
+            blobs.insert(sha.to_string(), path.to_string());
+        }
+    }
+    let shas: Vec<String> = blobs.keys().cloned().collect();
+    let sizes = blob_sizes(&shas)?;
+    let mut seen = Vec::new();
+    for (sha, size) in shas.iter().zip(sizes) {
+        let path = &blobs[sha];
+        if let Some(finding) = check_file(path, size, &policy) {
+            if seen.contains(path) {
+                continue;
+            }
+            seen.push(path.clone());
+            findings.push(finding);
+        }
+    }
+    findings.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    Ok(findings)
+}
+
+/// Prints blocking findings and fails unless `allow` is set.
+pub fn enforce(findings: &[Finding], allow: bool, action: &str) -> Result<()> {
+    let blocking: Vec<&Finding> = findings.iter().filter(|f| f.blocking).collect();
+    if blocking.is_empty() {
+        return Ok(());
+    }
+
+    if allow {
+        for finding in &blocking {
+            eprintln!("⚠ {}: {}", finding.location(), finding.message);
+        }
+        eprintln!("  Continuing anyway because of --allow-secrets.");
+        return Ok(());
+    }
+
+    eprintln!("✗ SupGIT stopped this {} to protect you:", action);
+    for finding in &blocking {
+        eprintln!("  - {}: {}", finding.location(), finding.message);
+    }
+    eprintln!("  Secrets that reach a remote must be treated as leaked and rotated.");
+    eprintln!("  Remove them (for example with 'supgit unstage <path>' and a .gitignore entry),");
+    eprintln!("  or pass --allow-secrets if these are false positives.");
+    bail!("{} blocked by the secret and large-file guard", action);
+}
+
+fn check_file(path: &str, size: Option<u64>, policy: &GuardPolicy) -> Option<Finding> {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path);
+    if is_env_file(name) {
+        return Some(Finding {
+            path: path.to_string(),
+            line: None,
+            message: "environment file (usually holds credentials)".to_string(),
+            blocking: true,
+        });
+    }
+
+    let size = size?;
+    let limit = policy.max_file_size();
+    if size > limit {
+        Some(Finding {
+            path: path.to_string(),
+            line: None,
+            message: format!(
+                "file is {}, above the {} limit",
+                format_size(size),
+                format_size(limit)
+            ),
+            blocking: true,
+        })
+    } else if size > LARGE_FILE_BYTES {
+        Some(Finding {
+            path: path.to_string(),
+            line: None,
+            message: format!("large file ({})", format_size(size)),
+            blocking: false,
+        })
+    } else {
+        None
+    }
+}
+
+fn is_env_file(name: &str) -> bool {
+    if name == ".env" {
+        return true;
+    }
+    let Some(suffix) = name.strip_prefix(".env.") else {
+        return false;
+    };
+    !matches!(suffix, "example" | "sample" | "template" | "dist")
+}
+
+fn scan_added_lines(diff: &str) -> Vec<Finding> {
+    let patterns: Vec<(&str, Regex)> = SECRET_PATTERNS
+        .iter()
+        .filter_map(|(label, pattern)| Some((*label, Regex::new(pattern).ok()?)))
+        .collect();
+
+    let mut findings = Vec::new();
+    let mut path = String::new();
+    let mut line_no = 0;
+    // File headers only appear before the first hunk of each file; inside a
+    // hunk, `+++ ` is an added line that starts with `++ `.
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_hunk = false;
+            continue;
+        }
+        if !in_hunk && let Some(rest) = line.strip_prefix("+++ ") {
+            path = rest.strip_prefix("b/").unwrap_or(rest).to_string();
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("@@ ") {
+            in_hunk = true;
+            line_no = header
+                .split_whitespace()
+                .find_map(|range| range.strip_prefix('+'))
+                .and_then(|range| range.split(',').next()?.parse().ok())
+                .unwrap_or(0);
+            continue;
+        }
+        let Some(added) = line.strip_prefix('+') else {
+            continue;
+        };
+
+        let mut finding = |message: String, blocking: bool| {
+            findings.push(Finding {
+                path: path.clone(),
+                line: Some(line_no),
+                message,
+                blocking,
+            });
+        };
+        if added.starts_with("<<<<<<< ") || added == "=======" || added.starts_with(">>>>>>> ") {
+            finding("merge conflict marker".to_string(), false);
+        }
+        let mut matched = false;
+        for (label, regex) in &patterns {
+            if regex.is_match(added) {
+                finding(format!("looks like a secret ({})", label), true);
+                matched = true;
+            }
+        }
+        if !matched && !skips_entropy(&path) && has_high_entropy_token(added) {
+            finding(
+                "high-entropy string (possible API key or token)".to_string(),
+                true,
+            );
+        }
+        line_no += 1;
+    }
+    findings
+}
+
+fn skips_entropy(path: &str) -> bool {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path);
+    ENTROPY_SKIPPED_FILES.contains(&name)
+}
+
+fn has_high_entropy_token(line: &str) -> bool {
+    line.split(|c: char| !(c.is_ascii_alphanumeric() || "+/=_-".contains(c)))
+        .filter(|token| token.len() >= ENTROPY_MIN_LENGTH)
+        .filter(|token| {
+            token.chars().any(|c| c.is_ascii_uppercase())
+                && token.chars().any(|c| c.is_ascii_lowercase())
+                && token.chars().any(|c| c.is_ascii_digit())
+        })
+        .any(|token| shannon_entropy(token) >= ENTROPY_THRESHOLD)
+}
+
+fn shannon_entropy(token: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in token.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = token.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Looks up object sizes with one `git cat-file --batch-check` call; missing
+/// or non-blob objects yield `None`.
+pub fn blob_sizes(objects: &[String]) -> Result<Vec<Option<u64>>> {
+    if objects.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut child = StdCommand::new("git")
+        .args(["cat-file", "--batch-check=%(objecttype) %(objectsize)"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("running git cat-file --batch-check")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = objects.join("\n") + "\n";
+        stdin
+            .write_all(input.as_bytes())
+            .context("writing to git cat-file")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("running git cat-file --batch-check")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git cat-file --batch-check failed:\n  {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .map(|line| {
+            let (kind, size) = line.split_once(' ')?;
+            if kind != "blob" {
+                return None;
+            }
+            size.parse().ok()
+        })
+        .collect())
+}
+
+fn git_output(args: &[&str]) -> Result<String> {
+    let output = StdCommand::new("git")
+        .args(args)
+        .output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Scans what a push of `refspec` (or the current branch) would send and
+/// blocks on findings unless `allow` is set.
+pub fn guard_push(refspec: Option<&str>, allow: bool) -> Result<()> {
+    let local = refspec
+        .and_then(|spec| spec.split(':').next())
+        .filter(|rev| !rev.is_empty())
+        .filter(|rev| git_output(&["rev-parse", "--verify", "--quiet", rev]).is_ok())
+        .unwrap_or("HEAD");
+    if git_output(&["rev-parse", "--verify", "--quiet", local]).is_err() {
+        return Ok(());
+    }
+    let findings = scan_outgoing(local)?;
+    for finding in findings.iter().filter(|f| !f.blocking) {
+        eprintln!("⚠ {}: {}", finding.location(), finding.message);
+    }
+    enforce(&findings, allow, "push")
+}
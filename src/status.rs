@@ -1,50 +1,297 @@
+use std::path::PathBuf;
 use std::process::Command as StdCommand;
 use std::sync::{LazyLock, RwLock};
 
 use anyhow::{Context, Result, bail};
+use serde::Serialize;
 
-use crate::git::NOT_IN_REPO_HINT;
+use crate::git::{NOT_IN_REPO_HINT, git_stdout};
 
-type PorcelainCache = RwLock<Option<Vec<(String, String)>>>;
+#[derive(Clone, Default, Serialize)]
+pub struct BranchHeader {
+    pub oid: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileState {
+    Unmodified,
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChanged,
+    Unmerged,
+    Untracked,
+}
+
+impl FileState {
+    fn from_code(code: char) -> Self {
+        match code {
+            'M' => FileState::Modified,
+            'A' => FileState::Added,
+            'D' => FileState::Deleted,
+            'R' => FileState::Renamed,
+            'C' => FileState::Copied,
+            'T' => FileState::TypeChanged,
+            'U' => FileState::Unmerged,
+            _ => FileState::Unmodified,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FileState::Unmodified => "unmodified",
+            FileState::Modified => "modified",
+            FileState::Added => "added",
+            FileState::Deleted => "deleted",
+            FileState::Renamed => "renamed",
+            FileState::Copied => "copied",
+            FileState::TypeChanged => "type-changed",
+            FileState::Unmerged => "unmerged",
+            FileState::Untracked => "untracked",
+        }
+    }
+
+    pub fn short_code(self) -> char {
+        match self {
+            FileState::Unmodified => ' ',
+            FileState::Modified => 'M',
+            FileState::Added => 'A',
+            FileState::Deleted => 'D',
+            FileState::Renamed => 'R',
+            FileState::Copied => 'C',
+            FileState::TypeChanged => 'T',
+            FileState::Unmerged => 'U',
+            FileState::Untracked => '?',
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictKind {
+    BothDeleted,
+    AddedByUs,
+    DeletedByThem,
+    AddedByThem,
+    DeletedByUs,
+    BothAdded,
+    BothModified,
+}
+
+impl ConflictKind {
+    fn from_xy(xy: &str) -> Self {
+        match xy {
+            "DD" => ConflictKind::BothDeleted,
+            "AU" => ConflictKind::AddedByUs,
+            "UD" => ConflictKind::DeletedByThem,
+            "UA" => ConflictKind::AddedByThem,
+            "DU" => ConflictKind::DeletedByUs,
+            "AA" => ConflictKind::BothAdded,
+            _ => ConflictKind::BothModified,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictKind::BothDeleted => "both deleted",
+            ConflictKind::AddedByUs => "added by us",
+            ConflictKind::DeletedByThem => "deleted by them",
+            ConflictKind::AddedByThem => "added by them",
+            ConflictKind::DeletedByUs => "deleted by us",
+            ConflictKind::BothAdded => "both added",
+            ConflictKind::BothModified => "both modified",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct SubmoduleState {
+    pub commit_changed: bool,
+    pub tracked_changes: bool,
+    pub untracked_changes: bool,
+}
+
+impl SubmoduleState {
+    fn parse(field: &str) -> Option<Self> {
+        let mut chars = field.chars();
+        if chars.next()? != 'S' {
+            return None;
+        }
+        Some(Self {
+            commit_changed: chars.next()? == 'C',
+            tracked_changes: chars.next()? == 'M',
+            untracked_changes: chars.next()? == 'U',
+        })
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct StatusEntry {
+    pub path: String,
+    pub orig_path: Option<String>,
+    pub index: FileState,
+    pub worktree: FileState,
+    pub submodule: Option<SubmoduleState>,
+    pub conflict: Option<ConflictKind>,
+}
+
+impl StatusEntry {
+    pub fn is_untracked(&self) -> bool {
+        self.worktree == FileState::Untracked
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        self.conflict.is_some()
+    }
+
+    pub fn is_staged(&self) -> bool {
+        !self.is_conflicted() && self.index != FileState::Unmodified
+    }
+
+    pub fn is_unstaged(&self) -> bool {
+        !self.is_conflicted() && !self.is_untracked() && self.worktree != FileState::Unmodified
+    }
+
+    /// Path as shown to users; renames and copies include their origin.
+    pub fn display_path(&self) -> String {
+        match self.orig_path {
+            Some(ref orig) => format!("{} -> {}", orig, self.path),
+            None => self.path.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PorcelainSnapshot {
+    branch: BranchHeader,
+    entries: Vec<StatusEntry>,
+}
+
+type PorcelainCache = RwLock<Option<PorcelainSnapshot>>;
 type RepoRootCache = RwLock<Option<String>>;
 
 static PORCELAIN_CACHE: LazyLock<PorcelainCache> = LazyLock::new(|| RwLock::new(None));
 static REPO_ROOT_CACHE: LazyLock<RepoRootCache> = LazyLock::new(|| RwLock::new(None));
 
-fn get_porcelain_lines_cached() -> Result<Vec<(String, String)>> {
+fn get_porcelain_snapshot_cached() -> Result<PorcelainSnapshot> {
     // Acquire write lock up front to avoid TOCTOU race
     // Use into_inner() to recover from poisoning
     let mut guard = PORCELAIN_CACHE.write().unwrap_or_else(|e| e.into_inner());
 
-    if let Some(ref entries) = *guard {
-        return Ok(entries.clone());
+    if let Some(ref snapshot) = *guard {
+        return Ok(snapshot.clone());
     }
 
     let output = StdCommand::new("git")
-        .args(["status", "--porcelain"])
+        .args(["status", "--porcelain=v2", "--branch", "-z"])
         .output()
-        .context("running git status --porcelain")?;
+        .context("running git status --porcelain=v2")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git status --porcelain failed: {}", stderr.trim());
+        bail!("git status --porcelain=v2 failed: {}", stderr.trim());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let entries: Vec<(String, String)> = stdout
-        .lines()
-        .filter_map(|line| {
-            if line.len() < 4 {
-                return None;
-            }
-            let status = line[..2].to_string();
-            let path = line[3..].to_string();
-            Some((status, path))
-        })
-        .collect();
+    let snapshot = parse_porcelain_v2(&stdout);
+    *guard = Some(snapshot.clone());
+    Ok(snapshot)
+}
+
+fn parse_porcelain_v2(output: &str) -> PorcelainSnapshot {
+    let mut branch = BranchHeader::default();
+    let mut entries = Vec::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            parse_branch_header(header, &mut branch);
+            continue;
+        }
+        let Some((kind, rest)) = record.split_once(' ') else {
+            continue;
+        };
+        let entry = match kind {
+            "1" => parse_changed_record(rest, 8, None),
+            // Renames and copies carry their origin path as the next record.
+            "2" => parse_changed_record(rest, 9, records.next()),
+            "u" => parse_unmerged_record(rest),
+            "?" => Some(StatusEntry {
+                path: rest.to_string(),
+                orig_path: None,
+                index: FileState::Unmodified,
+                worktree: FileState::Untracked,
+                submodule: None,
+                conflict: None,
+            }),
+            _ => None,
+        };
+        entries.extend(entry);
+    }
+
+    PorcelainSnapshot { branch, entries }
+}
+
+/// Parses `1 XY sub mH mI mW hH hI path` and
+/// `2 XY sub mH mI mW hH hI Xscore path` records.
+fn parse_changed_record(rest: &str, field_count: usize, orig: Option<&str>) -> Option<StatusEntry> {
+    let fields: Vec<&str> = rest.splitn(field_count, ' ').collect();
+    if fields.len() != field_count {
+        return None;
+    }
+    let mut xy = fields[0].chars();
+    Some(StatusEntry {
+        path: fields[field_count - 1].to_string(),
+        orig_path: orig.map(String::from),
+        index: FileState::from_code(xy.next()?),
+        worktree: FileState::from_code(xy.next()?),
+        submodule: SubmoduleState::parse(fields[1]),
+        conflict: None,
+    })
+}
 
-    *guard = Some(entries.clone());
-    Ok(entries)
+/// Parses `u XY sub m1 m2 m3 mW h1 h2 h3 path` records.
+fn parse_unmerged_record(rest: &str) -> Option<StatusEntry> {
+    let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+    if fields.len() != 10 {
+        return None;
+    }
+    Some(StatusEntry {
+        path: fields[9].to_string(),
+        orig_path: None,
+        index: FileState::Unmerged,
+        worktree: FileState::Unmerged,
+        submodule: SubmoduleState::parse(fields[1]),
+        conflict: Some(ConflictKind::from_xy(fields[0])),
+    })
+}
+
+fn parse_branch_header(header: &str, branch: &mut BranchHeader) {
+    let Some((key, value)) = header.split_once(' ') else {
+        return;
+    };
+    match key {
+        "branch.oid" if value != "(initial)" => branch.oid = Some(value.to_string()),
+        "branch.head" if value != "(detached)" => branch.head = Some(value.to_string()),
+        "branch.upstream" => branch.upstream = Some(value.to_string()),
+        "branch.ab" => {
+            for part in value.split(' ') {
+                if let Some(n) = part.strip_prefix('+') {
+                    branch.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = part.strip_prefix('-') {
+                    branch.behind = n.parse().unwrap_or(0);
+                }
+            }
+        }
+        _ => {}
+    }
 }
 
 pub fn invalidate_porcelain_cache() {
@@ -89,88 +336,131 @@ pub fn get_repo_root() -> Result<String> {
     }
 }
 
+pub fn get_git_dir() -> Result<PathBuf> {
+    let output = StdCommand::new("git")
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+
+    if !output.status.success() {
+        bail!("{}", NOT_IN_REPO_HINT);
+    }
+
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if path.is_empty() {
+        bail!("{}", NOT_IN_REPO_HINT);
+    }
+    Ok(PathBuf::from(path))
+}
+
 pub struct PorcelainStatus {
-    entries: Vec<(String, String)>,
+    branch: BranchHeader,
+    entries: Vec<StatusEntry>,
 }
 
 impl PorcelainStatus {
     pub fn parse() -> Result<Self> {
+        let snapshot = get_porcelain_snapshot_cached()?;
         Ok(Self {
-            entries: get_porcelain_lines_cached()?,
+            branch: snapshot.branch,
+            entries: snapshot.entries,
         })
     }
 
+    pub fn branch(&self) -> &BranchHeader {
+        &self.branch
+    }
+
+    /// Files with worktree changes and nothing staged yet.
     pub fn unstaged_files(&self) -> Vec<&str> {
         self.entries
             .iter()
-            .filter(|(status, _)| {
-                let xy: Vec<char> = status.chars().collect();
-                let x = xy.first().copied().unwrap_or(' ');
-                let y = xy.get(1).copied().unwrap_or(' ');
-                x == ' ' && y != ' ' && y != '?'
-            })
-            .map(|(_, path)| path.as_str())
+            .filter(|e| e.is_unstaged() && e.index == FileState::Unmodified)
+            .map(|e| e.path.as_str())
             .collect()
     }
 
-    pub fn all_uncommitted_files(&self) -> Vec<&str> {
-        self.entries.iter().map(|(_, path)| path.as_str()).collect()
+    pub fn entries(&self) -> &[StatusEntry] {
+        &self.entries
     }
 }
 
-pub fn get_porcelain_lines() -> Result<Vec<(String, String)>> {
-    get_porcelain_lines_cached()
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InProgress {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Bisect,
 }
 
-pub fn get_unstaged_files() -> Result<Vec<String>> {
-    let entries = get_porcelain_lines()?;
-    let files: Vec<String> = entries
-        .iter()
-        .filter(|(status, _)| {
-            let xy: Vec<char> = status.chars().collect();
-            let x = xy.first().copied().unwrap_or(' ');
-            let y = xy.get(1).copied().unwrap_or(' ');
-            x == ' ' && y != ' ' && y != '?'
-        })
-        .map(|(_, path)| path.clone())
-        .collect();
+impl InProgress {
+    pub fn label(self) -> &'static str {
+        match self {
+            InProgress::Merge => "merge",
+            InProgress::Rebase => "rebase",
+            InProgress::CherryPick => "cherry-pick",
+            InProgress::Revert => "revert",
+            InProgress::Bisect => "bisect",
+        }
+    }
+}
 
-    Ok(files)
+pub fn get_in_progress_operation() -> Result<Option<InProgress>> {
+    let git_dir = get_git_dir()?;
+    let operation =
+        if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+            Some(InProgress::Rebase)
+        } else if git_dir.join("MERGE_HEAD").exists() {
+            Some(InProgress::Merge)
+        } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+            Some(InProgress::CherryPick)
+        } else if git_dir.join("REVERT_HEAD").exists() {
+            Some(InProgress::Revert)
+        } else if git_dir.join("BISECT_LOG").exists() {
+            Some(InProgress::Bisect)
+        } else {
+            None
+        };
+    Ok(operation)
 }
 
-pub fn get_staged_files() -> Result<Vec<String>> {
-    let entries = get_porcelain_lines()?;
-    let files: Vec<String> = entries
-        .iter()
-        .filter(|(status, _)| {
-            let x = status.chars().next().unwrap_or(' ');
-            matches!(x, 'M' | 'A' | 'D' | 'R' | 'C')
-        })
-        .map(|(_, path)| path.clone())
-        .collect();
+pub fn get_stash_count() -> usize {
+    git_stdout(&["stash", "list"])
+        .map(|s| s.lines().count())
+        .unwrap_or(0)
+}
 
-    Ok(files)
+pub fn get_status_entries() -> Result<Vec<StatusEntry>> {
+    Ok(get_porcelain_snapshot_cached()?.entries)
 }
 
-pub fn get_all_uncommitted_files() -> Result<Vec<String>> {
-    let entries = get_porcelain_lines()?;
-    let files: Vec<String> = entries.iter().map(|(_, path)| path.clone()).collect();
-    Ok(files)
+pub fn get_unstaged_files() -> Result<Vec<String>> {
+    let status = PorcelainStatus::parse()?;
+    Ok(status
+        .unstaged_files()
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
+
+pub fn get_staged_files() -> Result<Vec<String>> {
+    let entries = get_status_entries()?;
+    Ok(entries
+        .into_iter()
+        .filter(StatusEntry::is_staged)
+        .map(|e| e.path)
+        .collect())
 }
 
 pub fn get_untracked_files() -> Result<Vec<String>> {
-    let entries = get_porcelain_lines()?;
-    let files: Vec<String> = entries
-        .iter()
-        .filter(|(status, _)| {
-            let xy: Vec<char> = status.chars().collect();
-            let x = xy.first().copied().unwrap_or(' ');
-            let y = xy.get(1).copied().unwrap_or(' ');
-            x == '?' && y == '?'
-        })
-        .map(|(_, path)| path.clone())
-        .collect();
-    Ok(files)
+    let entries = get_status_entries()?;
+    Ok(entries
+        .into_iter()
+        .filter(StatusEntry::is_untracked)
+        .map(|e| e.path)
+        .collect())
 }
 
 pub fn get_branches() -> Result<Vec<String>> {
@@ -189,6 +479,68 @@ pub fn get_branches() -> Result<Vec<String>> {
     Ok(branches)
 }
 
+#[derive(Serialize)]
+pub struct BranchInfo {
+    pub name: String,
+    pub current: bool,
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub upstream_gone: bool,
+}
+
+pub fn get_branch_details() -> Result<Vec<BranchInfo>> {
+    let output = StdCommand::new("git")
+        .args([
+            "for-each-ref",
+            "--format=%(HEAD)%00%(refname:short)%00%(upstream:short)%00%(upstream:track,nobracket)",
+            "refs/heads",
+        ])
+        .output()
+        .context("running git for-each-ref")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git for-each-ref failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let branches = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\0');
+            let head = parts.next()?;
+            let name = parts.next()?.to_string();
+            let upstream = parts.next().filter(|s| !s.is_empty()).map(String::from);
+            let track = parts.next().unwrap_or_default();
+            let (ahead, behind) = parse_track_counts(track);
+            Some(BranchInfo {
+                name,
+                current: head == "*",
+                upstream,
+                ahead,
+                behind,
+                upstream_gone: track == "gone",
+            })
+        })
+        .collect();
+
+    Ok(branches)
+}
+
+fn parse_track_counts(track: &str) -> (usize, usize) {
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind)
+}
+
 pub fn get_current_branch() -> Result<String> {
     let output = StdCommand::new("git")
         .args(["branch", "--show-current"])
@@ -198,3 +550,153 @@ pub fn get_current_branch() -> Result<String> {
     let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
     Ok(branch)
 }
+
+/// Remote-tracking refs (e.g. `origin/main`) that already contain `rev`,
+/// i.e. where the commit has been published.
+pub fn get_published_refs(rev: &str) -> Result<Vec<String>> {
+    let output = StdCommand::new("git")
+        .args([
+            "for-each-ref",
+            "--contains",
+            rev,
+            "--format=%(refname:short)",
+            "refs/remotes",
+        ])
+        .output()
+        .context("running git for-each-ref --contains")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git for-each-ref --contains failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|name| !name.ends_with("/HEAD"))
+        .map(String::from)
+        .collect())
+}
+
+pub struct CommitSummary {
+    pub hash: String,
+    pub short_hash: String,
+    pub subject: String,
+    pub age: String,
+}
+
+/// Recent non-merge commits on HEAD that no remote-tracking branch contains
+/// yet, newest first.
+pub fn get_unpushed_commits(limit: usize) -> Result<Vec<CommitSummary>> {
+    get_recent_commits(limit, true)
+}
+
+/// The last `limit` non-merge commits on HEAD, newest first, optionally
+/// restricted to ones not yet on any remote.
+pub fn get_recent_commits(limit: usize, unpushed_only: bool) -> Result<Vec<CommitSummary>> {
+    let limit_arg = format!("-n{}", limit);
+    let mut args = vec![
+        "log",
+        "--no-merges",
+        &limit_arg,
+        "--format=%H%x00%h%x00%s%x00%cr",
+        "HEAD",
+    ];
+    if unpushed_only {
+        args.extend(["--not", "--remotes"]);
+    }
+    let output = StdCommand::new("git")
+        .args(&args)
+        .output()
+        .context("running git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git log failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\0');
+            Some(CommitSummary {
+                hash: parts.next()?.to_string(),
+                short_hash: parts.next()?.to_string(),
+                subject: parts.next()?.to_string(),
+                age: parts.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(records: &[&str]) -> PorcelainSnapshot {
+        parse_porcelain_v2(&(records.join("\0") + "\0"))
+    }
+
+    #[test]
+    fn parses_branch_headers() {
+        let snapshot = parse(&[
+            "# branch.oid 1234abcd",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+        ]);
+        let branch = snapshot.branch;
+        assert_eq!(branch.oid.as_deref(), Some("1234abcd"));
+        assert_eq!(branch.head.as_deref(), Some("main"));
+        assert_eq!(branch.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((branch.ahead, branch.behind), (2, 1));
+
+        let unborn = parse(&["# branch.oid (initial)", "# branch.head (detached)"]).branch;
+        assert_eq!((unborn.oid, unborn.head), (None, None));
+    }
+
+    #[test]
+    fn keeps_spaces_in_paths() {
+        let snapshot = parse(&[
+            "1 .M N... 100644 100644 100644 aaaa bbbb docs/my notes.txt",
+            "? new folder/a b.txt",
+        ]);
+        let paths: Vec<&str> = snapshot.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["docs/my notes.txt", "new folder/a b.txt"]);
+        assert!(snapshot.entries[0].is_unstaged());
+        assert!(snapshot.entries[1].is_untracked());
+    }
+
+    #[test]
+    fn reads_rename_origin_from_next_record() {
+        let snapshot = parse(&[
+            "2 R. N... 100644 100644 100644 aaaa aaaa R100 new name.txt",
+            "old name.txt",
+            "1 M. N... 100644 100644 100644 aaaa bbbb other.txt",
+        ]);
+        assert_eq!(snapshot.entries.len(), 2);
+        let rename = &snapshot.entries[0];
+        assert_eq!(rename.path, "new name.txt");
+        assert_eq!(rename.orig_path.as_deref(), Some("old name.txt"));
+        assert!(rename.index == FileState::Renamed);
+        assert_eq!(rename.display_path(), "old name.txt -> new name.txt");
+        assert_eq!(snapshot.entries[1].path, "other.txt");
+    }
+
+    #[test]
+    fn parses_conflicts_and_submodules() {
+        let snapshot = parse(&[
+            "u AA N... 000000 100644 100644 100644 0000 aaaa bbbb both added.txt",
+            "u UD N... 100644 100644 000000 100644 aaaa bbbb 0000 gone.txt",
+            "1 .M SCM. 160000 160000 160000 aaaa aaaa vendor/lib",
+        ]);
+        let conflict = &snapshot.entries[0];
+        assert_eq!(conflict.path, "both added.txt");
+        assert!(conflict.is_conflicted());
+        assert!(conflict.conflict == Some(ConflictKind::BothAdded));
+        assert!(snapshot.entries[1].conflict == Some(ConflictKind::DeletedByThem));
+
+        let submodule = snapshot.entries[2].submodule.unwrap();
+        assert!(submodule.commit_changed && submodule.tracked_changes);
+        assert!(!submodule.untracked_changes);
+    }
+}